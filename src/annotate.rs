@@ -0,0 +1,142 @@
+use std::io::{BufRead, Write};
+
+use crate::sudoku::*;
+use crate::reader::*;
+use crate::writer::*;
+
+pub const ANNOTATION_COLUMNS: [&str; 4] = ["declared_clues", "solution_count", "computed_difficulty", "mismatch"];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Annotation {
+    pub solution_count: SolutionCount,
+    pub difficulty: Difficulty,
+    pub clues_mismatch: bool,
+    pub solution_mismatch: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct AnnotateSummary {
+    pub rows: usize,
+    pub skipped: usize,
+    pub flagged: usize,
+}
+
+pub fn solution_count_name(solution_count: &SolutionCount) -> &'static str {
+    match solution_count {
+        SolutionCount::Zero => "zero",
+        SolutionCount::One(_) => "one",
+        SolutionCount::Multiple(..) => "multiple",
+    }
+}
+
+// Solves and rates the puzzle, filling in the solution if the record has none,
+// and records the annotation columns in record.extra
+pub fn annotate_record(record: &mut PuzzleRecord, depth: usize) -> Annotation {
+    let mut puzzle = record.puzzle;
+    let solution_count = puzzle.solve(false);
+    let difficulty = record.puzzle.get_difficulty(depth);
+
+    // a declared clue count that doesn't parse is still a disagreement
+    let declared_clues = record.extra.get("clues").cloned();
+    let clues_mismatch = match &declared_clues {
+        Some(declared) => declared.parse::<usize>() != Ok(record.puzzle.clues),
+        None => false,
+    };
+    let solution_mismatch = match (record.solution, solution_count) {
+        (None, _) => false,
+        (Some(declared), SolutionCount::One(solution)) => declared.board != solution.board,
+        (Some(_), _) => true,
+    };
+    if let (None, SolutionCount::One(solution)) = (record.solution, solution_count) {
+        record.solution = Some(solution);
+    }
+
+    let mut mismatch = vec![];
+    if clues_mismatch {
+        mismatch.push("clues");
+    }
+    if solution_mismatch {
+        mismatch.push("solution");
+    }
+    record.extra.insert("declared_clues".to_string(), declared_clues.unwrap_or_default());
+    record.extra.insert("solution_count".to_string(), solution_count_name(&solution_count).to_string());
+    record.extra.insert("computed_difficulty".to_string(), format!("{:?}", difficulty));
+    record.extra.insert("mismatch".to_string(), mismatch.join(";"));
+
+    Annotation { solution_count, difficulty, clues_mismatch, solution_mismatch }
+}
+
+// Rows that fail to parse are handed to skip and left out of the output
+pub fn annotate<R, W, F>(reader: PuzzleReader<R>, output: W, depth: usize, mut skip: F) -> Result<AnnotateSummary, ReadError>
+where R: BufRead, W: Write, F: FnMut(ReadError) {
+    // keep any columns we don't know about, except clues which the writer recomputes
+    let mut extra_columns = reader.columns().iter()
+        .filter(|c| !COLUMNS.contains(&c.as_str()))
+        .cloned()
+        .collect::<Vec<_>>();
+    extra_columns.extend(ANNOTATION_COLUMNS.iter().map(|c| c.to_string()));
+    let mut writer = PuzzleWriter::with_columns(output, ',', extra_columns)?;
+
+    let mut summary = AnnotateSummary::default();
+    for record in reader {
+        let mut record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                skip(e);
+                summary.skipped += 1;
+                continue;
+            },
+        };
+        let annotation = annotate_record(&mut record, depth);
+        if annotation.clues_mismatch || annotation.solution_mismatch {
+            summary.flagged += 1;
+        }
+        writer.write_record(&record)?;
+        summary.rows += 1;
+    }
+    writer.flush()?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn fills_in_todo_solution() {
+        let reader = PuzzleReader::from_path("data(small)/arto-inkala-hardest.csv").unwrap();
+        let mut output = vec![];
+        let summary = annotate(reader, &mut output, 0, |e| panic!("skipped {}", e)).unwrap();
+        assert_eq!(summary, AnnotateSummary { rows: 1, skipped: 0, flagged: 0 });
+
+        let annotated = PuzzleReader::new(output.as_slice()).unwrap().next().unwrap().unwrap();
+        let solution = annotated.solution.expect("solution was filled in");
+        assert_eq!(solution.clues, 81);
+        assert_eq!(annotated.extra["declared_clues"], "21");
+        assert_eq!(annotated.extra["solution_count"], "one");
+        assert_eq!(annotated.extra["mismatch"], "");
+    }
+
+    #[test]
+    fn flags_disagreements() {
+        // the real solution with 1s and 2s swapped is a valid grid, but not this puzzle's solution
+        let input = format!("id,sudoku,solution,clues,difficulty\n1,{},{},27,1\n", PUZZLE, SWAPPED_SOLUTION);
+        let mut record = PuzzleReader::new(input.as_bytes()).unwrap().next().unwrap().unwrap();
+        let annotation = annotate_record(&mut record, 0);
+        assert!(annotation.clues_mismatch);
+        assert!(annotation.solution_mismatch);
+        assert_eq!(record.extra["mismatch"], "clues;solution");
+    }
+
+    #[test]
+    fn hands_back_skipped_rows() {
+        let input = format!("id,sudoku\n1,{}\n2,123\n", PUZZLE);
+        let reader = PuzzleReader::new(input.as_bytes()).unwrap();
+        let mut skipped = vec![];
+        let summary = annotate(reader, vec![], 0, |e| skipped.push(e.to_string())).unwrap();
+        assert_eq!(summary, AnnotateSummary { rows: 1, skipped: 1, flagged: 0 });
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].contains("line 3"), "{}", skipped[0]);
+    }
+}
@@ -0,0 +1,255 @@
+use std::fmt::Write;
+use std::time::Instant;
+
+use crate::sudoku::*;
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct BenchReport {
+    pub puzzles: usize,
+    pub unique: usize,
+    pub zero: usize,
+    pub multiple: usize,
+    pub total_millis: f64,
+    pub mean_millis: f64,
+    pub p50_millis: f64,
+    pub p90_millis: f64,
+    pub p99_millis: f64,
+    pub p99_9_millis: f64,
+    pub max_millis: f64,
+    pub puzzles_per_second: f64,
+    pub total_guesses: usize,
+    pub mean_guesses: f64,
+    pub max_guesses: usize,
+    // (index, millis), slowest first
+    pub slowest: Vec<(usize, f64)>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Regression {
+    pub metric: &'static str,
+    pub baseline: f64,
+    pub current: f64,
+}
+
+impl Regression {
+    pub fn percent_change(&self) -> f64 {
+        (self.current - self.baseline) / self.baseline * 100.0
+    }
+}
+
+// Nearest-rank percentile of an already sorted slice
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    // the epsilon stops 99.9% of 1000 rounding up to 1000 through float error
+    let rank = (percent / 100.0 * sorted.len() as f64 - 1e-9).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+pub fn run_bench(all_sudoku: &[Sudoku], slowest_count: usize) -> BenchReport {
+    let mut report = BenchReport { puzzles: all_sudoku.len(), ..Default::default() };
+    let mut timings = Vec::with_capacity(all_sudoku.len());
+    let bench_start = Instant::now();
+    for mut sudoku in all_sudoku.iter().copied() {
+        let start = Instant::now();
+        let solutions = sudoku.solve(false);
+        let millis = start.elapsed().as_nanos() as f64 / 1_000_000.0;
+        match solutions {
+            SolutionCount::Zero => report.zero += 1,
+            SolutionCount::One(_) => report.unique += 1,
+            SolutionCount::Multiple(..) => report.multiple += 1,
+        }
+        timings.push(millis);
+    }
+    let wall_seconds = bench_start.elapsed().as_secs_f64();
+
+    // tracking stats slows solving down, so guesses get their own untimed pass
+    for mut sudoku in all_sudoku.iter().copied() {
+        let guesses = sudoku.solve_with_stats(false).1.guesses;
+        report.total_guesses += guesses;
+        report.max_guesses = report.max_guesses.max(guesses);
+    }
+
+    let mut by_time = timings.iter().copied().enumerate().collect::<Vec<_>>();
+    by_time.sort_by(|a, b| b.1.total_cmp(&a.1));
+    report.slowest = by_time.into_iter().take(slowest_count).collect();
+
+    timings.sort_by(f64::total_cmp);
+    report.total_millis = timings.iter().sum();
+    if report.puzzles > 0 {
+        report.mean_millis = report.total_millis / report.puzzles as f64;
+        report.mean_guesses = report.total_guesses as f64 / report.puzzles as f64;
+    }
+    report.p50_millis = percentile(&timings, 50.0);
+    report.p90_millis = percentile(&timings, 90.0);
+    report.p99_millis = percentile(&timings, 99.0);
+    report.p99_9_millis = percentile(&timings, 99.9);
+    report.max_millis = timings.last().copied().unwrap_or(0.0);
+    if wall_seconds > 0.0 {
+        report.puzzles_per_second = report.puzzles as f64 / wall_seconds;
+    }
+    report
+}
+
+// Extracts a top level number from the flat JSON written by BenchReport::to_json
+fn json_number(json: &str, key: &str) -> Option<f64> {
+    let start = json.find(&format!("\"{}\":", key))? + key.len() + 3;
+    let rest = json[start..].trim_start();
+    let end = rest.find([',', '}', '\n']).unwrap_or(rest.len());
+    rest[..end].trim().parse().ok()
+}
+
+impl BenchReport {
+    fn numbers(&self) -> [(&'static str, f64); 15] {
+        [
+            ("puzzles", self.puzzles as f64),
+            ("unique", self.unique as f64),
+            ("zero", self.zero as f64),
+            ("multiple", self.multiple as f64),
+            ("total_millis", self.total_millis),
+            ("mean_millis", self.mean_millis),
+            ("p50_millis", self.p50_millis),
+            ("p90_millis", self.p90_millis),
+            ("p99_millis", self.p99_millis),
+            ("p99_9_millis", self.p99_9_millis),
+            ("max_millis", self.max_millis),
+            ("puzzles_per_second", self.puzzles_per_second),
+            ("total_guesses", self.total_guesses as f64),
+            ("mean_guesses", self.mean_guesses),
+            ("max_guesses", self.max_guesses as f64),
+        ]
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut res = String::from("{\n");
+        for (key, value) in self.numbers() {
+            writeln!(res, "  \"{}\": {},", key, value).expect("writing to a String");
+        }
+        let slowest = self.slowest.iter().map(|(i, ms)| format!("[{}, {}]", i, ms)).collect::<Vec<_>>();
+        writeln!(res, "  \"slowest\": [{}]", slowest.join(", ")).expect("writing to a String");
+        res.push('}');
+        res
+    }
+
+    // Only the summary numbers are read back, the slowest list is left empty
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let get = |key: &str| json_number(json, key).ok_or(format!("baseline is missing {:?}", key));
+        Ok(Self {
+            puzzles: get("puzzles")? as usize,
+            unique: get("unique")? as usize,
+            zero: get("zero")? as usize,
+            multiple: get("multiple")? as usize,
+            total_millis: get("total_millis")?,
+            mean_millis: get("mean_millis")?,
+            p50_millis: get("p50_millis")?,
+            p90_millis: get("p90_millis")?,
+            p99_millis: get("p99_millis")?,
+            p99_9_millis: get("p99_9_millis")?,
+            max_millis: get("max_millis")?,
+            puzzles_per_second: get("puzzles_per_second")?,
+            total_guesses: get("total_guesses")? as usize,
+            mean_guesses: get("mean_guesses")?,
+            max_guesses: get("max_guesses")? as usize,
+            slowest: vec![],
+        })
+    }
+
+    // tolerance is a fraction, so 0.1 allows metrics to get 10% worse
+    pub fn regressions(&self, baseline: &BenchReport, tolerance: f64) -> Vec<Regression> {
+        let lower_is_better = [
+            ("mean_millis", baseline.mean_millis, self.mean_millis),
+            ("p50_millis", baseline.p50_millis, self.p50_millis),
+            ("p90_millis", baseline.p90_millis, self.p90_millis),
+            ("p99_millis", baseline.p99_millis, self.p99_millis),
+            ("mean_guesses", baseline.mean_guesses, self.mean_guesses),
+        ];
+        let mut res = vec![];
+        for (metric, baseline, current) in lower_is_better {
+            if current > baseline * (1.0 + tolerance) {
+                res.push(Regression { metric, baseline, current });
+            }
+        }
+        if self.puzzles_per_second < baseline.puzzles_per_second * (1.0 - tolerance) {
+            res.push(Regression { metric: "puzzles_per_second", baseline: baseline.puzzles_per_second, current: self.puzzles_per_second });
+        }
+        res
+    }
+
+    pub fn print(&self) {
+        println!("Solved {} / {} puzzles", self.unique, self.puzzles);
+        if self.zero > 0 {
+            println!("{} puzzles had no solutions", self.zero);
+        }
+        if self.multiple > 0 {
+            println!("{} puzzles had multiple solutions", self.multiple);
+        }
+        println!("Throughput: {:.1} puzzles/s", self.puzzles_per_second);
+        println!("Average time: {:.4}ms", self.mean_millis);
+        println!("p50: {:.4}ms  p90: {:.4}ms  p99: {:.4}ms  p99.9: {:.4}ms  max: {:.4}ms",
+            self.p50_millis, self.p90_millis, self.p99_millis, self.p99_9_millis, self.max_millis);
+        println!("Guesses: {} total, {:.2} average, {} max", self.total_guesses, self.mean_guesses, self.max_guesses);
+        if !self.slowest.is_empty() {
+            println!("Slowest puzzles:");
+            for (index, millis) in &self.slowest {
+                println!("Index: {}\tTook {:.4}ms", index, millis);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        let sorted = (1..=1000).map(|x| x as f64).collect::<Vec<_>>();
+        assert_eq!(percentile(&sorted, 50.0), 500.0);
+        assert_eq!(percentile(&sorted, 99.9), 999.0);
+        assert_eq!(percentile(&sorted, 100.0), 1000.0);
+        assert_eq!(percentile(&[3.0], 90.0), 3.0);
+        assert_eq!(percentile(&[], 90.0), 0.0);
+    }
+
+    #[test]
+    fn reports_counts_and_slowest() {
+        let all_sudoku = [
+            Sudoku::from_string(PUZZLE),
+            Sudoku::from_string(&".".repeat(81)),
+        ];
+        let report = run_bench(&all_sudoku, 5);
+        assert_eq!(report.puzzles, 2);
+        assert_eq!(report.unique, 1);
+        assert_eq!(report.multiple, 1);
+        assert_eq!(report.slowest.len(), 2);
+        assert!(report.total_guesses > 0);
+    }
+
+    #[test]
+    fn json_round_trip_and_regressions() {
+        let report = BenchReport {
+            puzzles: 10,
+            unique: 10,
+            mean_millis: 1.5,
+            p50_millis: 1.25,
+            p90_millis: 2.0,
+            p99_millis: 4.0,
+            puzzles_per_second: 600.0,
+            mean_guesses: 3.0,
+            slowest: vec![(4, 8.5), (1, 2.0)],
+            ..Default::default()
+        };
+        let json = report.to_json();
+        assert!(json.contains("\"slowest\": [[4, 8.5], [1, 2]]"));
+        let read = BenchReport::from_json(&json).unwrap();
+        assert_eq!(read, BenchReport { slowest: vec![], ..report.clone() });
+        assert!(report.regressions(&read, 0.1).is_empty());
+
+        let slower = BenchReport { p90_millis: 3.0, puzzles_per_second: 400.0, ..report.clone() };
+        let metrics = slower.regressions(&report, 0.1).iter().map(|r| r.metric).collect::<Vec<_>>();
+        assert_eq!(metrics, vec!["p90_millis", "puzzles_per_second"]);
+        assert!(BenchReport::from_json("{}").is_err());
+    }
+}
@@ -0,0 +1,494 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::str::FromStr;
+
+use sudoku_project::sudoku::*;
+use sudoku_project::sudoku::parse::ParseError;
+use sudoku_project::sudoku::solve::solve_batch;
+use sudoku_project::reader::*;
+use sudoku_project::writer::*;
+use sudoku_project::annotate;
+use sudoku_project::bench;
+use sudoku_project::packed::{self, PackedCorpus};
+
+// Exit codes; when several puzzles are checked the worst one wins
+pub const EXIT_UNIQUE: i32 = 0;
+pub const EXIT_ERROR: i32 = 1;
+pub const EXIT_NO_SOLUTION: i32 = 2;
+pub const EXIT_MULTIPLE: i32 = 3;
+
+const USAGE: &str = "\
+usage: sudoku_project <command> [options] [inputs...]
+
+commands:
+    solve [--grid] [--threads N]      print the solution of every puzzle
+    rate [--depth N]                  print the difficulty and hardest technique needed for every puzzle
+    generate [--clues N] [--attempts N]
+                                      reduce the first input's solution to minimal unique puzzles (CSV on stdout)
+    bench [--slowest N] [--save-baseline FILE] [--baseline FILE] [--tolerance PCT]
+                                      time the solver over every puzzle, optionally comparing
+                                      against a JSON baseline (exit code 1 on regression)
+    validate [--threads N]            check every puzzle has exactly one solution
+    annotate <input> <output> [--depth N]
+                                      fill in solutions, clue counts and ratings for a CSV file
+    pack <input> <output.sdkb>        convert puzzles to the packed binary format
+
+--threads spreads solving over N worker threads (0 for every core), output stays in input order
+
+inputs are 81 character puzzle strings, sudokuwiki.org links, paths to CSV files or files with one puzzle per line
+(optionally gzip or zstd compressed), packed .sdkb files, or - for stdin (the default)
+
+exit codes: 0 every puzzle has a unique solution, 1 usage or input error,
+            2 some puzzle has no solution, 3 some puzzle has multiple solutions";
+
+// options that take a value, and the switches that don't; anything else starting with --<letter> is an error
+const VALUE_OPTIONS: [&str; 8] = ["depth", "clues", "attempts", "threads", "slowest", "save-baseline", "baseline", "tolerance"];
+const SWITCHES: [&str; 2] = ["grid", "help"];
+
+// records read ahead of the solver when solving with --threads
+const READ_CHUNK: usize = 16384;
+
+type Records = Box<dyn Iterator<Item = Result<PuzzleRecord, ReadError>>>;
+
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut positional = vec![];
+        let mut options = HashMap::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            // a grid passed as an argument starts with its ----- border, so that stays positional
+            let Some(name) = arg.strip_prefix("--").filter(|name| name.starts_with(|c: char| c.is_ascii_alphabetic())) else {
+                positional.push(arg.clone());
+                continue;
+            };
+            if let Some((name, value)) = name.split_once('=') {
+                if !VALUE_OPTIONS.contains(&name) {
+                    return Err(format!("--{} doesn't take a value", name));
+                }
+                options.insert(name.to_string(), value.to_string());
+            } else if VALUE_OPTIONS.contains(&name) {
+                let value = iter.next().ok_or(format!("--{} needs a value", name))?;
+                options.insert(name.to_string(), value.clone());
+            } else if SWITCHES.contains(&name) {
+                options.insert(name.to_string(), String::new());
+            } else {
+                return Err(format!("unknown option --{}", name));
+            }
+        }
+        Ok(Self { positional, options })
+    }
+
+    fn get<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.options.get(name) {
+            Some(value) => value.parse().map_err(|_| format!("invalid value for --{}: {:?}", name, value)),
+            None => Ok(default),
+        }
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+}
+
+pub fn run(args: &[String]) -> i32 {
+    let Some(command) = args.first() else {
+        eprintln!("{}", USAGE);
+        return EXIT_ERROR;
+    };
+    let args = match Args::parse(&args[1..]) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_ERROR;
+        },
+    };
+    let result = match command.as_str() {
+        "solve" => solve(&args),
+        "rate" => rate(&args),
+        "generate" => generate(&args),
+        "bench" => bench(&args),
+        "validate" => validate(&args),
+        "annotate" => run_annotate(&args),
+        "pack" => pack(&args),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(EXIT_UNIQUE)
+        },
+        _ => Err(format!("unknown command {:?}\n{}", command, USAGE)),
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{}", e);
+            EXIT_ERROR
+        },
+    }
+}
+
+fn worst(a: i32, b: i32) -> i32 {
+    let rank = |code| match code {
+        EXIT_UNIQUE => 0,
+        EXIT_MULTIPLE => 1,
+        EXIT_NO_SOLUTION => 2,
+        _ => 3,
+    };
+    if rank(b) > rank(a) { b } else { a }
+}
+
+fn exit_code(solution_count: &SolutionCount) -> i32 {
+    match solution_count {
+        SolutionCount::Zero => EXIT_NO_SOLUTION,
+        SolutionCount::One(_) => EXIT_UNIQUE,
+        SolutionCount::Multiple(..) => EXIT_MULTIPLE,
+    }
+}
+
+fn label(record: &PuzzleRecord, index: usize) -> String {
+    match &record.id {
+        Some(id) => id.clone(),
+        None => (index + 1).to_string(),
+    }
+}
+
+// e.g. "differing at cells 3 (1 or 5) and 12 (5 or 1)"
+fn describe_differences(first: &Sudoku, second: &Sudoku) -> String {
+    let cells = first.differences(second).iter()
+        .map(|&cell| format!("{} ({} or {})", cell, first.board[cell % 9][cell / 9], second.board[cell % 9][cell / 9]))
+        .collect::<Vec<_>>();
+    match cells.split_last() {
+        Some((last, [])) => format!("differing at cell {}", last),
+        Some((last, rest)) => format!("differing at cells {} and {}", rest.join(", "), last),
+        None => "differing nowhere".to_string(),
+    }
+}
+
+fn open_inputs(inputs: &[String]) -> Result<Records, String> {
+    if inputs.is_empty() {
+        return open_input("-");
+    }
+    let mut res: Records = Box::new(std::iter::empty());
+    for input in inputs {
+        res = Box::new(res.chain(open_input(input)?));
+    }
+    Ok(res)
+}
+
+fn open_input(input: &str) -> Result<Records, String> {
+    match Sudoku::parse(input) {
+        Ok(puzzle) => return Ok(Box::new(std::iter::once(Ok(PuzzleRecord::new(puzzle))))),
+        Err(e) => {
+            // something shaped like a puzzle that isn't a file is a bad puzzle, not a missing file
+            let looks_like_puzzle = input.chars().count() == 81 || !matches!(e, ParseError::WrongLength(_));
+            if looks_like_puzzle && !Path::new(input).exists() {
+                return Err(format!("{}: {}", input, e));
+            }
+        },
+    }
+    if input.starts_with("http://") || input.starts_with("https://") {
+        let puzzle = Sudoku::from_sudoku_wiki_link(input).map_err(|e| format!("{}: {}", input, e))?;
+        return Ok(Box::new(std::iter::once(Ok(PuzzleRecord::new(puzzle)))));
+    }
+    if input.ends_with(".sdkb") {
+        let corpus = PackedCorpus::open(input).map_err(|e| format!("{}: {}", input, e))?;
+        // packed puzzles are numbered from 1 like the rows of the file they came from
+        return Ok(Box::new((0..corpus.len()).map(move |i| {
+            let puzzle = corpus.get(i).expect("index is in range");
+            puzzle.map(PuzzleRecord::new).map_err(|error| ReadError::Parse { line: i + 1, error })
+        })));
+    }
+    let reader = PuzzleReader::from_path(input).map_err(|e| format!("{}: {}", input, e))?;
+    Ok(Box::new(reader))
+}
+
+// Reads records in chunks and solves each chunk with solve_batch, so a large file is
+// spread across threads without being held in memory; handle sees records in input order
+fn for_each_solved<F>(mut records: Records, threads: usize, mut handle: F)
+where F: FnMut(usize, Result<(PuzzleRecord, SolutionCount), ReadError>) {
+    let mut index = 0;
+    loop {
+        let chunk = records.by_ref().take(READ_CHUNK).collect::<Vec<_>>();
+        if chunk.is_empty() {
+            return;
+        }
+        let puzzles = chunk.iter().filter_map(|record| record.as_ref().ok()).map(|record| record.puzzle).collect::<Vec<_>>();
+        let mut solved = solve_batch(&puzzles, threads).into_iter();
+        for record in chunk {
+            let result = record.map(|record| (record, solved.next().expect("one result per puzzle")));
+            handle(index, result);
+            index += 1;
+        }
+    }
+}
+
+fn solve(args: &Args) -> Result<i32, String> {
+    let grid = args.flag("grid");
+    let threads = args.get("threads", 1)?;
+    let mut code = EXIT_UNIQUE;
+    for_each_solved(open_inputs(&args.positional)?, threads, |index, solved| {
+        let (record, solution_count) = match solved {
+            Ok(solved) => solved,
+            Err(e) => {
+                eprintln!("{}", e);
+                code = worst(code, EXIT_ERROR);
+                return;
+            },
+        };
+        code = worst(code, exit_code(&solution_count));
+        match solution_count {
+            SolutionCount::One(solution) if grid => print!("{}", solution),
+            SolutionCount::One(solution) => println!("{}", solution.to_digit_string()),
+            SolutionCount::Zero => println!("{}: no solution", label(&record, index)),
+            SolutionCount::Multiple(first, second) => {
+                println!("{}: multiple solutions, {}", label(&record, index), describe_differences(&first, &second));
+            },
+        }
+    });
+    Ok(code)
+}
+
+fn rate(args: &Args) -> Result<i32, String> {
+    let depth = args.get("depth", 2)?;
+    let mut code = EXIT_UNIQUE;
+    for (index, record) in open_inputs(&args.positional)?.enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                eprintln!("{}", e);
+                code = worst(code, EXIT_ERROR);
+                continue;
+            },
+        };
+        let difficulty = record.puzzle.get_difficulty(depth);
+        match difficulty {
+            Difficulty::LiterallyZeroSolutions => code = worst(code, EXIT_NO_SOLUTION),
+            Difficulty::LiterallyMultipleSolutions => code = worst(code, EXIT_MULTIPLE),
+            _ => {},
+        }
+        // the hardest technique only grades the puzzle when logic alone solves it
+        let (_, stats) = Sudoku::from_sudoku(&record.puzzle).solve_with_stats(false);
+        let technique = match stats.hardest_technique() {
+            _ if stats.guesses > 0 => "needs guessing",
+            Some(technique) => technique.name(),
+            None => "no technique needed",
+        };
+        println!("{}: {:?}, {}", label(&record, index), difficulty, technique);
+    }
+    Ok(code)
+}
+
+fn validate(args: &Args) -> Result<i32, String> {
+    let threads = args.get("threads", 1)?;
+    let mut code = EXIT_UNIQUE;
+    let mut checked = 0;
+    let mut failed = 0;
+    for_each_solved(open_inputs(&args.positional)?, threads, |index, solved| {
+        checked += 1;
+        let (record, solution_count) = match solved {
+            Ok(solved) => solved,
+            Err(e) => {
+                eprintln!("{}", e);
+                failed += 1;
+                code = worst(code, EXIT_ERROR);
+                return;
+            },
+        };
+        code = worst(code, exit_code(&solution_count));
+        let problem = match (solution_count, record.solution) {
+            (SolutionCount::Zero, _) => Some("no solution".to_string()),
+            (SolutionCount::Multiple(first, second), _) => {
+                Some(format!("multiple solutions, {}", describe_differences(&first, &second)))
+            },
+            (SolutionCount::One(solution), Some(declared)) if solution.board != declared.board => {
+                code = worst(code, EXIT_ERROR);
+                Some(format!("solution does not match, solver found {}", solution.to_digit_string()))
+            },
+            (SolutionCount::One(_), _) => None,
+        };
+        if let Some(problem) = problem {
+            println!("{}: {}", label(&record, index), problem);
+            failed += 1;
+        }
+    });
+    println!("{} / {} puzzles are valid", checked - failed, checked);
+    Ok(code)
+}
+
+fn bench(args: &Args) -> Result<i32, String> {
+    let slowest = args.get("slowest", 10)?;
+    let tolerance = args.get("tolerance", 10.0)? / 100.0;
+    let all_sudoku = open_inputs(&args.positional)?
+        .map(|record| record.map(|record| record.puzzle))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let report = bench::run_bench(&all_sudoku, slowest);
+    report.print();
+
+    if let Some(path) = args.options.get("save-baseline") {
+        fs::write(path, report.to_json()).map_err(|e| format!("{}: {}", path, e))?;
+        println!("Saved baseline to {}", path);
+    }
+    let mut code = EXIT_UNIQUE;
+    if let Some(path) = args.options.get("baseline") {
+        let json = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let baseline = bench::BenchReport::from_json(&json).map_err(|e| format!("{}: {}", path, e))?;
+        if baseline.puzzles != report.puzzles {
+            println!("Warning: baseline has {} puzzles, this run has {}", baseline.puzzles, report.puzzles);
+        }
+        let regressions = report.regressions(&baseline, tolerance);
+        for regression in &regressions {
+            println!("REGRESSION {}: {:.4} -> {:.4} ({:+.1}%)",
+                regression.metric, regression.baseline, regression.current, regression.percent_change());
+        }
+        if regressions.is_empty() {
+            println!("No regressions against {} (tolerance {}%)", path, tolerance * 100.0);
+        } else {
+            code = EXIT_ERROR;
+        }
+    }
+    if report.zero > 0 {
+        code = worst(code, EXIT_NO_SOLUTION);
+    }
+    if report.multiple > 0 {
+        code = worst(code, EXIT_MULTIPLE);
+    }
+    Ok(code)
+}
+
+fn generate(args: &Args) -> Result<i32, String> {
+    let clues = args.get("clues", 28)?;
+    let attempts = args.get("attempts", 1000)?;
+    let seed = open_inputs(&args.positional)?.next()
+        .ok_or("generate needs a puzzle to start from")?
+        .map_err(|e| e.to_string())?;
+    let mut to_solve = seed.puzzle;
+    let mut sudoku = match to_solve.solve(false) {
+        SolutionCount::One(solution) => solution,
+        other => return Ok(exit_code(&other)),
+    };
+
+    let mut generated_queue = vec![];
+    let mut generated_set = BTreeSet::new();
+    for attempt in 0..attempts {
+        let reduced = sudoku.reduce_to_n_random(clues);
+        let mut to_solve = Sudoku::from_sudoku(&reduced);
+        if let SolutionCount::One(_) = to_solve.solve(false) {
+            eprintln!("Found a puzzle with {} clues on attempt {}/{}", clues, attempt, attempts);
+            generated_queue.push(reduced);
+            generated_set.insert(reduced);
+            break;
+        }
+    }
+    if generated_set.is_empty() {
+        return Err(format!("no unique puzzle with {} clues found in {} attempts", clues, attempts));
+    }
+    while let Some(sudoku) = generated_queue.pop() {
+        for reduced in sudoku.get_all_reduced_by_one_clue() {
+            if generated_set.insert(reduced) {
+                generated_queue.push(reduced);
+            }
+        }
+    }
+
+    let fewest_clues = generated_set.iter().map(|s| s.clues).min().expect("set is not empty");
+    eprintln!("Generated {} puzzles, the smallest have {} clues", generated_set.len(), fewest_clues);
+    let mut writer = PuzzleWriter::new(io::stdout().lock()).map_err(|e| e.to_string())?;
+    for sudoku in generated_set.iter().filter(|s| s.clues == fewest_clues) {
+        writer.write_sudoku(sudoku).map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(EXIT_UNIQUE)
+}
+
+fn run_annotate(args: &Args) -> Result<i32, String> {
+    let [input, output] = args.positional.as_slice() else {
+        return Err("usage: annotate <input> <output> [--depth N]".to_string());
+    };
+    let depth = args.get("depth", 2)?;
+    let reader = PuzzleReader::from_path(input).map_err(|e| format!("{}: {}", input, e))?;
+    let file = File::create(output).map_err(|e| format!("{}: {}", output, e))?;
+    let summary = annotate::annotate(reader, BufWriter::new(file), depth, |e| eprintln!("skipping row: {}", e))
+        .map_err(|e| e.to_string())?;
+    println!("Annotated {} rows ({} skipped, {} flagged)", summary.rows, summary.skipped, summary.flagged);
+    Ok(if summary.skipped > 0 || summary.flagged > 0 { EXIT_ERROR } else { EXIT_UNIQUE })
+}
+
+fn pack(args: &Args) -> Result<i32, String> {
+    let [input, output] = args.positional.as_slice() else {
+        return Err("usage: pack <input> <output.sdkb>".to_string());
+    };
+    let count = packed::pack_file(input, output).map_err(|e| format!("{}: {}", input, e))?;
+    println!("Packed {} puzzles into {}", count, output);
+    Ok(EXIT_UNIQUE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the library's shared fixtures are test-only, so the binary reads the same puzzle from disk
+    fn basic_puzzle() -> Sudoku {
+        get_first_sudoku_from_path("data(small)/basic-test.csv").expect("basic-test.csv has a puzzle")
+    }
+
+    fn args(args: &[&str]) -> Args {
+        Args::parse(&args.iter().map(|s| s.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn parses_options_and_positionals() {
+        let args = args(&["--depth", "3", "a.csv", "--grid", "--clues=25", "-"]);
+        assert_eq!(args.positional, vec!["a.csv", "-"]);
+        assert_eq!(args.get("depth", 0), Ok(3));
+        assert_eq!(args.get("clues", 0), Ok(25));
+        assert_eq!(args.get("attempts", 7), Ok(7));
+        assert!(args.flag("grid"));
+        assert!(!args.flag("threads"));
+    }
+
+    #[test]
+    fn rejects_unknown_options() {
+        let parse = |args: &[&str]| Args::parse(&args.iter().map(|s| s.to_string()).collect::<Vec<_>>());
+        assert_eq!(parse(&["--grdi"]).err(), Some("unknown option --grdi".to_string()));
+        assert_eq!(parse(&["--thread", "4"]).err(), Some("unknown option --thread".to_string()));
+        assert!(parse(&["--grid=yes"]).is_err());
+        // a Display grid is an input, not an option
+        let grid = basic_puzzle().to_string();
+        assert_eq!(parse(&[&grid]).unwrap().positional, vec![grid.clone()]);
+        assert_eq!(run(&["solve".to_string(), grid]), EXIT_UNIQUE);
+    }
+
+    #[test]
+    fn worst_exit_code_wins() {
+        assert_eq!(worst(EXIT_UNIQUE, EXIT_MULTIPLE), EXIT_MULTIPLE);
+        assert_eq!(worst(EXIT_NO_SOLUTION, EXIT_MULTIPLE), EXIT_NO_SOLUTION);
+        assert_eq!(worst(EXIT_ERROR, EXIT_NO_SOLUTION), EXIT_ERROR);
+    }
+
+    #[test]
+    fn solve_exit_codes() {
+        let solved = basic_puzzle().to_digit_string();
+        let empty = ".".repeat(81);
+        let run_solve = |input: &str| run(&["solve".to_string(), input.to_string()]);
+        assert_eq!(run_solve(&solved), EXIT_UNIQUE);
+        assert_eq!(run_solve(&empty), EXIT_MULTIPLE);
+        assert_eq!(run_solve("no/such/file.csv"), EXIT_ERROR);
+    }
+
+    #[test]
+    fn bad_puzzles_report_the_parse_error() {
+        // two 2s in the first row
+        let mut duplicate = basic_puzzle().to_digit_string();
+        duplicate.replace_range(1..2, "2");
+        let error = open_input(&duplicate).err().expect("not a puzzle or a file");
+        assert!(error.contains("digit 2"), "{}", error);
+        let short = &duplicate[..80];
+        assert!(open_input(short).err().expect("not a puzzle or a file").contains("No such file"));
+    }
+}
@@ -0,0 +1,16 @@
+// Grids shared by the unit tests. PUZZLE is the first row of data(small)/basic-test.csv
+pub const PUZZLE: &str = "200070038000006070300040600008020700100000006007030400004080009060400000910060002";
+pub const SOLUTION: &str = "246975138589316274371248695498621753132754986657839421724183569865492317913567842";
+
+// PUZZLE with a second 2 in the top row, at cell 1
+pub const DUPLICATE_TWO: &str = "220070038000006070300040600008020700100000006007030400004080009060400000910060002";
+
+// SOLUTION with every 1 and 2 swapped: a solved grid, but not PUZZLE's solution
+pub const SWAPPED_SOLUTION: &str = "146975238589326174372148695498612753231754986657839412714283569865491327923567841";
+
+// SOLUTION with a 2/7 rectangle in the bottom rows blanked out (cells 68, 71, 77 and 80),
+// so it has exactly two solutions
+pub const TWO_SOLUTIONS: &str = "24697513858931627437124869549862175313275498665783942172418356986549.31.91356.84.";
+
+// Row 5851 of data(small)/sudoku17.csv, solved without guessing once X-Wing is available
+pub const X_WING: &str = "000070900020600000500000300040000021700030000000000000000201080900000700000400000";
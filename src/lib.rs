@@ -0,0 +1,79 @@
+//! Sudoku parsing, solving, rating and corpus tools.
+//!
+//! The stable surface is:
+//! - [`Sudoku`], [`Tile`], [`SolutionCount`] and [`Difficulty`], with parsing in [`parse`]
+//!   ([`Sudoku::parse`], [`ParseError`]), solving in [`solve`] and printing in [`print`](mod@print)
+//! - [`SolveStats`] from [`Sudoku::solve_with_stats`], broken down by [`Technique`]
+//! - [`Unit`] and the checks in [`validate`] ([`Sudoku::conflicts`], [`Sudoku::is_solved`])
+//! - [`reader`] and [`writer`] for the id,sudoku,solution,clues,difficulty CSV schema
+//! - [`packed`] for the binary corpus format
+//!
+//! [`annotate`] and [`bench`](mod@bench) back the command line tool and may change with it.
+//!
+//! ```
+//! use sudoku_project::{Sudoku, SolutionCount};
+//!
+//! let mut sudoku: Sudoku = "2...7..38.....6.7.3...4.6....8.2.7..1.......6..7.3.4....4.8...9.6.4.....91..6...2".parse().unwrap();
+//! match sudoku.solve(false) {
+//!     SolutionCount::One(solution) => assert_eq!(solution.clues, 26),
+//!     _ => unreachable!(),
+//! }
+//! ```
+
+pub mod sudoku;
+pub mod reader;
+pub mod writer;
+pub mod annotate;
+pub mod bench;
+pub mod packed;
+
+#[cfg(test)]
+mod fixtures;
+
+pub use sudoku::{Sudoku, Tile, SolutionCount, Difficulty, Unit};
+pub use sudoku::{parse, print, solve, stats, validate};
+pub use sudoku::parse::ParseError;
+pub use sudoku::validate::Conflict;
+pub use sudoku::stats::{SolveStats, Technique};
+
+#[cfg(test)]
+mod tests {
+    use crate::reader::*;
+    use crate::sudoku::*;
+    #[test]
+    fn test_solver() {
+        let sudoku_puzzles = get_all_sudoku_from_path("data/sudoku17.csv");
+        let mut index = 0;
+        for mut sudoku in sudoku_puzzles {
+            let puzzle = sudoku;
+            if let SolutionCount::One(solution) = sudoku.solve(false) {
+                if !solution.is_solution_of(&puzzle) {
+                    panic!("Given solution is invalid");
+                }
+            } else {
+                panic!("Solver found wrong number of solutions at index {}", index);
+            }
+            index += 1;
+            if index >= 100 {
+                // Limits solving to 100 Sudoku for time reasons
+                return;
+            }
+        }
+    }
+    #[test]
+    fn test_multiple() {
+        let sudoku_puzzles = get_all_sudoku_from_path("data/multiple.csv");
+        let mut index = 0;
+        for mut sudoku in sudoku_puzzles {
+            if let SolutionCount::Multiple(..) = sudoku.solve(false) {
+                // good
+            } else {
+                panic!("Solver found wrong number of solutions");
+            }
+            index += 1;
+            if index >= 100 {
+                // Limits solving to 100 Sudoku for time reasons
+                return;
+            }
+        }    }
+}
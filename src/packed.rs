@@ -0,0 +1,202 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+
+use memmap2::Mmap;
+
+use crate::sudoku::*;
+use crate::sudoku::parse::ParseError;
+use crate::reader::*;
+
+// Packed corpus layout, all integers little endian:
+//   header: b"SDKB", version (u16), reserved (u16), puzzle count (u64)
+//   then one 41 byte record per puzzle, 4 bits per cell in reading order
+//   (high nibble first, 0 for an empty cell, the last low nibble is unused)
+// Records are fixed size, so puzzle i starts at HEADER_LEN + i * RECORD_LEN
+pub const MAGIC: [u8; 4] = *b"SDKB";
+pub const VERSION: u16 = 1;
+pub const HEADER_LEN: usize = 16;
+pub const RECORD_LEN: usize = 41;
+const COUNT_OFFSET: u64 = 8;
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub fn pack_sudoku(sudoku: &Sudoku) -> [u8; RECORD_LEN] {
+    let mut res = [0; RECORD_LEN];
+    for i in 0..81 {
+        let nibble = match sudoku.board[i % 9][i / 9] {
+            Tile::Void => 0,
+            Tile::Num(num) => num as u8,
+        };
+        res[i / 2] |= if i % 2 == 0 { nibble << 4 } else { nibble };
+    }
+    res
+}
+
+// Goes through Sudoku::parse so a corrupt record is reported instead of building a broken board
+pub fn unpack_sudoku(record: &[u8]) -> Result<Sudoku, ParseError> {
+    let mut digits = [0; 81];
+    for (i, digit) in digits.iter_mut().enumerate() {
+        let byte = record[i / 2];
+        let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+        *digit = b'0' + nibble;
+    }
+    // nibbles above 9 become characters after '9', which parse rejects
+    Sudoku::parse(std::str::from_utf8(&digits).expect("all bytes are ascii"))
+}
+
+pub struct PackedWriter<W: Write + Seek> {
+    inner: W,
+    count: u64,
+}
+
+impl PackedWriter<BufWriter<File>> {
+    pub fn create(path: &str) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write + Seek> PackedWriter<W> {
+    // The header is written with a count of 0 and patched by finish
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        inner.write_all(&MAGIC)?;
+        inner.write_all(&VERSION.to_le_bytes())?;
+        inner.write_all(&[0; 2])?;
+        inner.write_all(&0u64.to_le_bytes())?;
+        Ok(Self { inner, count: 0 })
+    }
+
+    pub fn write_sudoku(&mut self, sudoku: &Sudoku) -> io::Result<()> {
+        self.inner.write_all(&pack_sudoku(sudoku))?;
+        self.count += 1;
+        Ok(())
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.inner.seek(SeekFrom::Start(COUNT_OFFSET))?;
+        self.inner.write_all(&self.count.to_le_bytes())?;
+        self.inner.seek(SeekFrom::End(0))?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+// Read only view of a packed corpus, memory mapped when opened from a file
+pub struct PackedCorpus<D: AsRef<[u8]> = Mmap> {
+    data: D,
+    len: usize,
+}
+
+impl PackedCorpus<Mmap> {
+    pub fn open(path: &str) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: the map is read only, and like every other reader here we assume
+        // nobody truncates or rewrites the file while it is open
+        let data = unsafe { Mmap::map(&file)? };
+        Self::from_bytes(data)
+    }
+}
+
+impl<D: AsRef<[u8]>> PackedCorpus<D> {
+    pub fn from_bytes(data: D) -> io::Result<Self> {
+        let bytes = data.as_ref();
+        if bytes.len() < HEADER_LEN || bytes[..4] != MAGIC {
+            return Err(invalid_data("not a packed sudoku corpus".to_string()));
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != VERSION {
+            return Err(invalid_data(format!("unsupported packed corpus version {}", version)));
+        }
+        let count = u64::from_le_bytes(bytes[8..16].try_into().expect("8 bytes"));
+        let expected = (count as usize).checked_mul(RECORD_LEN).and_then(|n| n.checked_add(HEADER_LEN));
+        if expected != Some(bytes.len()) {
+            return Err(invalid_data(format!("header says {} puzzles but the file is {} bytes", count, bytes.len())));
+        }
+        Ok(Self { data, len: count as usize })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<Result<Sudoku, ParseError>> {
+        if index >= self.len {
+            return None;
+        }
+        let start = HEADER_LEN + index * RECORD_LEN;
+        Some(unpack_sudoku(&self.data.as_ref()[start..start + RECORD_LEN]))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Result<Sudoku, ParseError>> + '_ {
+        (0..self.len).map(|i| self.get(i).expect("index is in range"))
+    }
+}
+
+// Converts any input PuzzleReader understands, stopping at the first bad row
+// so puzzle indices in the packed file match the source rows
+pub fn pack_file(input: &str, output: &str) -> Result<u64, ReadError> {
+    let mut writer = PackedWriter::create(output)?;
+    for record in PuzzleReader::from_path(input)? {
+        writer.write_sudoku(&record?.puzzle)?;
+    }
+    let count = writer.count();
+    writer.finish()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use crate::fixtures::*;
+
+    #[test]
+    fn packs_four_bits_per_cell() {
+        let packed = pack_sudoku(&Sudoku::from_string(PUZZLE));
+        assert_eq!(packed[..3], [0x20, 0x00, 0x70]);
+        assert_eq!(packed[40], 0x20);
+        assert_eq!(unpack_sudoku(&packed).unwrap(), Sudoku::from_string(PUZZLE));
+    }
+
+    #[test]
+    fn round_trips_a_corpus() {
+        let all_sudoku = get_all_sudoku_from_path("data(small)/sudoku17.csv").into_iter().take(200).collect::<Vec<_>>();
+        let mut writer = PackedWriter::new(Cursor::new(vec![])).unwrap();
+        for sudoku in &all_sudoku {
+            writer.write_sudoku(sudoku).unwrap();
+        }
+        let bytes = writer.finish().unwrap().into_inner();
+        assert_eq!(bytes.len(), HEADER_LEN + all_sudoku.len() * RECORD_LEN);
+
+        let corpus = PackedCorpus::from_bytes(bytes).unwrap();
+        assert_eq!(corpus.len(), all_sudoku.len());
+        assert_eq!(corpus.get(17).unwrap().unwrap(), all_sudoku[17]);
+        assert!(corpus.get(all_sudoku.len()).is_none());
+        let read = corpus.iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(read, all_sudoku);
+    }
+
+    #[test]
+    fn rejects_bad_files() {
+        assert!(PackedCorpus::from_bytes(b"sudoku\n".to_vec()).is_err());
+
+        let mut writer = PackedWriter::new(Cursor::new(vec![])).unwrap();
+        writer.write_sudoku(&Sudoku::from_string(PUZZLE)).unwrap();
+        let mut bytes = writer.finish().unwrap().into_inner();
+        assert!(PackedCorpus::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        // two 2s in the first row, like DUPLICATE_TWO
+        bytes[HEADER_LEN] = 0x22;
+        let corpus = PackedCorpus::from_bytes(bytes).unwrap();
+        assert!(matches!(corpus.get(0), Some(Err(ParseError::DuplicateDigit { digit: 2, .. }))));
+    }
+}
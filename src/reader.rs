@@ -1,53 +1,235 @@
-use std::fs;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+
+use flate2::bufread::MultiGzDecoder;
 
 use crate::sudoku::*;
+use crate::sudoku::parse::ParseError;
 
-pub fn get_first_sudoku_from_path(path: &str) -> Option<Sudoku>  {
-    let file_path = path;
-    let binding = fs::read_to_string(file_path).expect("pls work");
-    let mut lines = binding.lines().clone();
-
-    // first line is "sudoku" or "*,sudoku,*"
-    let names = lines.next().expect("not None").split(',').collect::<Vec<_>>();
-    let sudoku_index = names.iter().position(|&r| r == "sudoku").expect("pls work");
-    let difficulty_index = names.iter().position(|&r| r == "difficulty");
-
-    let next_line = lines.next();
-    if next_line == None {
-        return None;
-    }
-    let split = next_line.expect("not None").split(',').collect::<Vec<_>>();
-    let string = split[sudoku_index];
-    let sudoku = Sudoku::from_string(string);
-    return Some(sudoku);
+#[derive(Clone, PartialEq, Debug)]
+pub struct PuzzleRecord {
+    pub id: Option<String>,
+    pub puzzle: Sudoku,
+    pub solution: Option<Sudoku>,
+    pub rating: Option<f32>,
+    // every column other than id, sudoku, solution and difficulty, keyed by header name
+    pub extra: HashMap<String, String>,
+}
+
+impl PuzzleRecord {
+    pub fn new(puzzle: Sudoku) -> Self {
+        Self { id: None, puzzle, solution: None, rating: None, extra: HashMap::new() }
+    }
 }
 
-    pub fn get_all_sudoku_from_path(path: &str) -> Vec<Sudoku>  {
-    let file_path = path;
-    let binding = fs::read_to_string(file_path).expect("pls work");
-    let mut lines = binding.lines().clone();
-
-    // first line is "sudoku" or "*,sudoku,*"
-    let names = lines.next().expect("not None").split(',').collect::<Vec<_>>();
-    let sudoku_index = names.iter().position(|&r| r == "sudoku").expect("pls work");
-    let difficulty_index = names.iter().position(|&r| r == "difficulty");
-
-    let mut res = vec![];
-    loop {
-        let next_line = lines.next();
-        if next_line == None {
-            return res;
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    MissingHeader,
+    MissingSudokuColumn,
+    // line numbers are 1-based and count the header
+    MissingField { line: usize },
+    Parse { line: usize, error: ParseError },
+    InvalidRating { line: usize, value: String },
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "{}", e),
+            ReadError::MissingHeader => write!(f, "input is empty, expected a header line or a puzzle"),
+            ReadError::MissingSudokuColumn => write!(f, "header has no \"sudoku\" column"),
+            ReadError::MissingField { line } => write!(f, "line {}: missing sudoku field", line),
+            ReadError::Parse { line, error } => write!(f, "line {}: {}", line, error),
+            ReadError::InvalidRating { line, value } => write!(f, "line {}: invalid difficulty {:?}", line, value),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+// Lazily reads puzzles one line at a time, so only the current row is held in memory
+pub struct PuzzleReader<R: BufRead> {
+    lines: io::Lines<R>,
+    line_number: usize,
+    delimiter: char,
+    columns: Vec<String>,
+    // None when the input has no header and is one puzzle per line
+    sudoku_index: Option<usize>,
+    id_index: Option<usize>,
+    solution_index: Option<usize>,
+    difficulty_index: Option<usize>,
+    // first puzzle of a headerless input, read while looking for a header
+    pending: Option<String>,
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+// "-" reads stdin, and gzip or zstd input (detected from the first bytes) is decompressed on the fly
+pub fn open_path(path: &str) -> io::Result<Box<dyn BufRead>> {
+    let input: Box<dyn Read> = if path == "-" {
+        Box::new(io::stdin())
+    } else {
+        Box::new(File::open(path)?)
+    };
+    decompress(BufReader::new(input))
+}
+
+fn decompress<R: BufRead + 'static>(mut input: R) -> io::Result<Box<dyn BufRead>> {
+    let magic = input.fill_buf()?;
+    if magic.starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(input))))
+    } else if magic.starts_with(&ZSTD_MAGIC) {
+        Ok(Box::new(BufReader::new(zstd::Decoder::with_buffer(input)?)))
+    } else {
+        Ok(Box::new(input))
+    }
+}
+
+impl PuzzleReader<Box<dyn BufRead>> {
+    pub fn from_path(path: &str) -> Result<Self, ReadError> {
+        Self::new(open_path(path)?)
+    }
+}
+
+// A line of a headerless file: the puzzle is the first word, anything after whitespace or # is ignored
+fn parse_bare_line(line: &str) -> Result<Sudoku, ParseError> {
+    let before_comment = line.split('#').next().unwrap_or_default();
+    let puzzle = before_comment.split_whitespace().next().unwrap_or_default();
+    Sudoku::parse(puzzle)
+}
+
+impl<R: BufRead> PuzzleReader<R> {
+    pub fn new(input: R) -> Result<Self, ReadError> {
+        let mut res = Self {
+            lines: input.lines(),
+            line_number: 0,
+            delimiter: ',',
+            columns: vec![],
+            sudoku_index: None,
+            id_index: None,
+            solution_index: None,
+            difficulty_index: None,
+            pending: None,
+        };
+
+        // first line is "sudoku" or "*,sudoku,*" (tab separated files are also accepted),
+        // or straight away a puzzle if there is no header
+        let header = res.next_line().ok_or(ReadError::MissingHeader)??;
+        res.delimiter = if header.contains('\t') { '\t' } else { ',' };
+        let names = header.split(res.delimiter).collect::<Vec<_>>();
+        res.sudoku_index = names.iter().position(|&r| r == "sudoku");
+        if res.sudoku_index.is_none() {
+            if parse_bare_line(&header).is_err() {
+                return Err(ReadError::MissingSudokuColumn);
+            }
+            res.pending = Some(header);
+            return Ok(res);
+        }
+        res.id_index = names.iter().position(|&r| r == "id");
+        res.solution_index = names.iter().position(|&r| r == "solution");
+        res.difficulty_index = names.iter().position(|&r| r == "difficulty");
+        res.columns = names.iter().map(|name| name.to_string()).collect();
+        Ok(res)
+    }
+
+    // Skips blank lines and # comments
+    fn next_line(&mut self) -> Option<io::Result<String>> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            self.line_number += 1;
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            return Some(Ok(line));
+        }
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    fn parse_line(&self, line: &str) -> Result<PuzzleRecord, ReadError> {
+        let line_number = self.line_number;
+        let Some(sudoku_index) = self.sudoku_index else {
+            let puzzle = parse_bare_line(line).map_err(|error| ReadError::Parse { line: line_number, error })?;
+            return Ok(PuzzleRecord::new(puzzle));
+        };
+        let split = line.split(self.delimiter).collect::<Vec<_>>();
+        // empty fields are treated the same as missing ones
+        let field = |index: Option<usize>| index.and_then(|i| split.get(i)).copied().filter(|s| !s.is_empty());
+
+        let string = field(Some(sudoku_index)).ok_or(ReadError::MissingField { line: line_number })?;
+        let puzzle = Sudoku::parse(string).map_err(|error| ReadError::Parse { line: line_number, error })?;
+
+        let id = field(self.id_index).map(|s| s.to_string());
+        // unsolved datasets use TODO as a placeholder solution
+        let solution = match field(self.solution_index) {
+            None | Some("TODO") => None,
+            Some(s) => Some(Sudoku::parse(s).map_err(|error| ReadError::Parse { line: line_number, error })?),
+        };
+        let rating = match field(self.difficulty_index) {
+            None => None,
+            Some(s) => Some(s.parse::<f32>().map_err(|_| ReadError::InvalidRating { line: line_number, value: s.to_string() })?),
+        };
+
+        let mut extra = HashMap::new();
+        for (i, name) in self.columns.iter().enumerate() {
+            let known = [self.sudoku_index, self.id_index, self.solution_index, self.difficulty_index];
+            if known.contains(&Some(i)) {
+                continue;
+            }
+            if let Some(value) = split.get(i) {
+                extra.insert(name.clone(), value.to_string());
+            }
         }
-        let split = next_line.expect("not None").split(',').collect::<Vec<_>>();
-        let string = split[sudoku_index];
-        let sudoku = Sudoku::from_string(string);
-        res.push(sudoku);
+
+        Ok(PuzzleRecord { id, puzzle, solution, rating, extra })
+    }
+}
+
+impl<R: BufRead> Iterator for PuzzleReader<R> {
+    type Item = Result<PuzzleRecord, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = match self.pending.take() {
+            Some(line) => line,
+            None => match self.next_line()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            },
+        };
+        Some(self.parse_line(&line))
     }
 }
 
+pub fn get_first_sudoku_from_path(path: &str) -> Option<Sudoku>  {
+    let mut reader = PuzzleReader::from_path(path).expect("pls work");
+    reader.next().map(|record| record.expect("pls work").puzzle)
+}
+
+pub fn get_all_sudoku_from_path(path: &str) -> Vec<Sudoku>  {
+    let reader = PuzzleReader::from_path(path).expect("pls work");
+    reader.map(|record| record.expect("pls work").puzzle).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures::*;
 
     #[test]
     fn reads_in_sudoku_correctly() {
@@ -66,18 +248,89 @@ mod tests {
         let sudoku = get_all_sudoku_from_path("data/sudoku17.csv");
         assert_eq!(sudoku.len(), 49151);
     }
-}
 
+    #[test]
+    fn puzzle_reader_streams_records() {
+        let input = format!("id,sudoku\n1,{}\n2,{}\n", PUZZLE, SOLUTION);
+        let records = PuzzleReader::new(input.as_bytes()).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].puzzle.clues, 26);
+    }
 
-/*
-    let split = lines.next().expect("pls").split(',').collect::<Vec<_>>();
-    let string = split[0];
-    let mut sudoku = Sudoku::from_string(string);
-    sudoku.print_with_possibilities();
-    println!("{}", sudoku);
-    let solutions = sudoku.solve(true);
-    match solutions {
-        SolutionCount::Zero | SolutionCount::Multiple => println!("{:?}", solutions),
-        SolutionCount::One(sud) => sud.print_with_possibilities(),
+    #[test]
+    fn puzzle_reader_reports_bad_rows_and_keeps_going() {
+        let input = format!("sudoku\nnot a sudoku\n\n{}\n", PUZZLE);
+        let mut reader = PuzzleReader::new(input.as_bytes()).unwrap();
+        assert!(matches!(reader.next(), Some(Err(ReadError::Parse { line: 2, .. }))));
+        assert!(matches!(reader.next(), Some(Ok(_))));
+        assert!(reader.next().is_none());
     }
-*/
+
+    #[test]
+    fn puzzle_reader_reads_all_columns() {
+        let mut reader = PuzzleReader::from_path("data(small)/arto-inkala-hardest.csv").unwrap();
+        let record = reader.next().unwrap().unwrap();
+        assert_eq!(record.id.as_deref(), Some("1"));
+        assert_eq!(record.puzzle.clues, 21);
+        assert_eq!(record.solution, None);
+        assert_eq!(record.rating, Some(10.0));
+        assert_eq!(record.extra.get("clues").map(String::as_str), Some("21"));
+        assert_eq!(record.extra.len(), 1);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn puzzle_reader_parses_solution_column() {
+        let input = format!("sudoku,solution\n{},{}\n", PUZZLE, SOLUTION);
+        let record = PuzzleReader::new(input.as_bytes()).unwrap().next().unwrap().unwrap();
+        assert_eq!(record.id, None);
+        assert_eq!(record.solution.unwrap().clues, 81);
+        assert_eq!(record.rating, None);
+    }
+
+    #[test]
+    fn puzzle_reader_decompresses_gzip_and_zstd() {
+        use std::io::{Cursor, Write};
+        use flate2::{write::GzEncoder, Compression};
+
+        let input = format!("sudoku\n{}\n", PUZZLE);
+        let mut gzip = GzEncoder::new(vec![], Compression::default());
+        gzip.write_all(input.as_bytes()).unwrap();
+        let gzip = gzip.finish().unwrap();
+        let zstd = zstd::encode_all(input.as_bytes(), 0).unwrap();
+
+        for compressed in [gzip, zstd, input.as_bytes().to_vec()] {
+            let reader = PuzzleReader::new(decompress(Cursor::new(compressed)).unwrap()).unwrap();
+            let records = reader.collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(records.len(), 1);
+            assert_eq!(records[0].puzzle.clues, 26);
+        }
+    }
+
+    #[test]
+    fn puzzle_reader_reads_headerless_lines() {
+        // the same puzzle with dots, with a mix of empty cell characters, plain, and cut short
+        let input = format!(
+            "# collection from a forum post\n\n{} # easy\n{}\t1.5\n{}\n{}\n",
+            PUZZLE.replace('0', "."),
+            "2-_-7*-38***_*6-7-3*-*4*6_-_*8*2_7-_1-*---__6-*7_3-4*_-_4-8**_9-6*4-_-**91-*6__-2",
+            PUZZLE,
+            &PUZZLE[..80],
+        );
+        let mut reader = PuzzleReader::new(input.as_bytes()).unwrap();
+        assert!(reader.columns().is_empty());
+        for _ in 0..3 {
+            let record = reader.next().unwrap().unwrap();
+            assert_eq!(record.puzzle.clues, 26);
+            assert_eq!(record.id, None);
+        }
+        assert!(matches!(reader.next(), Some(Err(ReadError::Parse { line: 6, .. }))));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn puzzle_reader_needs_sudoku_column() {
+        assert!(matches!(PuzzleReader::new("id,puzzle\n".as_bytes()), Err(ReadError::MissingSudokuColumn)));
+        assert!(matches!(PuzzleReader::new("".as_bytes()), Err(ReadError::MissingHeader)));
+    }
+}
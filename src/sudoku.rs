@@ -1,8 +1,13 @@
 pub mod solve;
 pub mod print;
+pub mod parse;
+pub mod validate;
+pub mod stats;
+#[cfg(feature = "serde")]
+mod serialize;
 
 use std::cmp::Ordering;
-use rand::{Rng, seq::SliceRandom, thread_rng};
+use rand::{seq::SliceRandom, thread_rng};
 
 // 9 lowest bits are true
 const ALL_POSSIBLE: u16 =  0b0000000111111111;
@@ -35,13 +40,18 @@ impl PartialOrd for Tile {
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "status", content = "solution", rename_all = "snake_case"))]
 pub enum SolutionCount {
     Zero,
     One(Sudoku),
-    Multiple,
+    // two of the solutions, which differ in at least one cell
+    Multiple(Sudoku, Sudoku),
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "level", content = "count", rename_all = "snake_case"))]
 pub enum Difficulty {
     Trivial,
     Easy,
@@ -53,6 +63,48 @@ pub enum Difficulty {
     TooDeep,
 }
 
+// Rows and columns are indexed by y and x, boxes left to right then top to bottom
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Unit {
+    Row(usize),
+    Column(usize),
+    Box(usize),
+}
+
+impl Unit {
+    // every row, then every column, then every box
+    pub fn all() -> impl Iterator<Item = Unit> {
+        (0..9).map(Unit::Row).chain((0..9).map(Unit::Column)).chain((0..9).map(Unit::Box))
+    }
+
+    pub fn containing(x: usize, y: usize) -> [Unit; 3] {
+        [Unit::Row(y), Unit::Column(x), Unit::Box(x / 3 + 3 * (y / 3))]
+    }
+
+    // Cells as (x, y), ordered so the k-th cell is bit k of the unit's row/column/box_possible masks
+    pub fn cells(self) -> [(usize, usize); 9] {
+        let mut res = [(0, 0); 9];
+        for (k, cell) in res.iter_mut().enumerate() {
+            *cell = match self {
+                Unit::Row(y) => (k, y),
+                Unit::Column(x) => (x, k),
+                Unit::Box(b) => (b % 3 * 3 + k % 3, b / 3 * 3 + k / 3),
+            };
+        }
+        res
+    }
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Unit::Row(y) => write!(f, "row {}", y + 1),
+            Unit::Column(x) => write!(f, "column {}", x + 1),
+            Unit::Box(b) => write!(f, "box {}", b + 1),
+        }
+    }
+}
+
 impl std::fmt::Display for Tile {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -74,15 +126,12 @@ pub struct Sudoku {
 }
 
 impl Sudoku {
-    fn get_tile_from_digit(digit: u8) -> Tile {
-        if digit == b'.' {
-            return Tile::Void;
+    fn get_tile_from_digit(digit: u8) -> Option<Tile> {
+        match digit {
+            b'.' | b'0' | b'-' | b'*' | b'_' => Some(Tile::Void),
+            b'1'..=b'9' => Some(Tile::Num((digit - b'0').into())),
+            _ => None,
         }
-        let digit = digit - 48;  // ASCII '0'
-        if digit == 0 {
-            return Tile::Void;
-        }
-        Tile::Num(digit.into())
     }
     fn new_blank() -> Self {
         let board = [[Tile::Void; 9]; 9];
@@ -93,21 +142,7 @@ impl Sudoku {
         Self { board, possible, column_possible, row_possible, box_possible, clues: 0 }
     }
     pub fn from_string(input: &str) -> Self {
-        if input.len() != 81 {
-            panic!("not the right length");
-        }
-        let mut res = Self::new_blank();
-        for i in 0..81 {
-            let x_pos = i % 9;
-            let y_pos = i / 9;
-            let digit = input.as_bytes()[i];
-            let tile = Self::get_tile_from_digit(digit);
-            res.set_tile_at(x_pos, y_pos, tile);
-            if tile != Tile::Void {
-                res.clues += 1;
-            }
-        }
-        return res;
+        Self::parse(input).unwrap_or_else(|e| panic!("{}", e))
     }
     pub fn from_sudoku(s: &Self) -> Self {
         let board = s.board.clone();
@@ -153,6 +188,35 @@ impl Sudoku {
         }
     }
 
+    // Rebuilds column_possible, row_possible and box_possible from possible
+    fn recompute_unit_possibilities(&mut self) {
+        self.column_possible = [[NONE_POSSIBLE; 9]; 9];
+        self.row_possible = [[NONE_POSSIBLE; 9]; 9];
+        self.box_possible = [[NONE_POSSIBLE; 9]; 9];
+        for x in 0..9 {
+            for y in 0..9 {
+                let box_index = x / 3 + 3 * (y / 3);
+                let index_in_box = x % 3 + 3 * (y % 3);
+                for num in 1..=9 {
+                    if self.possible[x][y] & (1 << (num - 1)) != 0 {
+                        self.column_possible[x][num - 1] |= 1 << y;
+                        self.row_possible[y][num - 1] |= 1 << x;
+                        self.box_possible[box_index][num - 1] |= 1 << index_in_box;
+                    }
+                }
+            }
+        }
+    }
+
+    // Where num can still go in the unit, bit k standing for the k-th of unit.cells()
+    pub fn unit_possible(&self, unit: Unit, num: usize) -> u16 {
+        match unit {
+            Unit::Row(y) => self.row_possible[y][num - 1],
+            Unit::Column(x) => self.column_possible[x][num - 1],
+            Unit::Box(b) => self.box_possible[b][num - 1],
+        }
+    }
+
     #[inline]
     pub fn is_possible_at(&mut self, x: usize, y: usize, num: usize) -> bool {
         let mask = 1 << (num - 1);
@@ -233,6 +297,7 @@ impl Sudoku {
         return res;
     }
 
+    #[allow(unused_variables)]
     pub fn get_difficulty(&self, depth: usize) -> Difficulty {
         if depth > 0 {
             let cheat = self.get_difficulty(depth - 1);
@@ -252,7 +317,7 @@ impl Sudoku {
         let solution_count = new.solve(false);
         match solution_count {
             SolutionCount::Zero => return Difficulty::LiterallyZeroSolutions,
-            SolutionCount::Multiple => return Difficulty::LiterallyMultipleSolutions,
+            SolutionCount::Multiple(..) => return Difficulty::LiterallyMultipleSolutions,
             SolutionCount::One(solution) => {
                 if depth < 1 {
                     return Difficulty::TooDeep;
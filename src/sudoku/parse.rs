@@ -0,0 +1,356 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::sudoku::*;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    WrongLength(usize),
+    // positions are 0-based indices into the input
+    InvalidCharacter { position: usize, character: char },
+    // positions are cell indices in reading order (y * 9 + x)
+    DuplicateDigit { digit: usize, first: usize, second: usize },
+    // number of cells found in a candidate grid
+    WrongCandidateCount(usize),
+    // a link without a bd= board parameter
+    MissingBoard,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::WrongLength(len) => write!(f, "expected 81 cells, found {}", len),
+            ParseError::InvalidCharacter { position, character } => {
+                write!(f, "invalid character {:?} at position {}", character, position)
+            },
+            ParseError::DuplicateDigit { digit, first, second } => {
+                write!(f, "digit {} appears twice in a unit (cells {} and {})", digit, first, second)
+            },
+            ParseError::WrongCandidateCount(len) => {
+                write!(f, "expected 81 space separated cells or 729 characters of candidates, found {} cells", len)
+            },
+            ParseError::MissingBoard => write!(f, "link has no bd= board parameter"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Characters that only lay out a multi-line grid; '-' is a separator here rather than an empty cell
+fn is_grid_separator(c: char) -> bool {
+    c.is_whitespace() || "|-+=:!".contains(c) || ('\u{2500}'..='\u{257f}').contains(&c)
+}
+
+impl Sudoku {
+    // Accepts a single line of 81 cells, or a grid spread over several lines (such as the Display output)
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let trimmed = input.trim();
+        if trimmed.contains('\n') {
+            return Self::parse_grid(input);
+        }
+        Self::parse_line(trimmed)
+    }
+
+    // Lines with no cells are borders, and within the other lines the
+    // separators are dropped, leaving the cells in reading order
+    fn parse_grid(input: &str) -> Result<Self, ParseError> {
+        let is_cell = |c: char| c != '-' && u8::try_from(c).ok().and_then(Self::get_tile_from_digit).is_some();
+        let mut cells = String::with_capacity(81);
+        let mut position = 0;
+        for line in input.split_inclusive('\n') {
+            let is_border = !line.chars().any(is_cell);
+            for character in line.chars() {
+                if !is_border && !is_grid_separator(character) {
+                    if u8::try_from(character).ok().and_then(Self::get_tile_from_digit).is_none() {
+                        return Err(ParseError::InvalidCharacter { position, character });
+                    }
+                    cells.push(character);
+                }
+                position += 1;
+            }
+        }
+        Self::parse_line(&cells)
+    }
+
+    fn parse_line(input: &str) -> Result<Self, ParseError> {
+        let length = input.chars().count();
+        if length != 81 {
+            return Err(ParseError::WrongLength(length));
+        }
+        let mut res = Self::new_blank();
+        for (i, character) in input.chars().enumerate() {
+            let tile = u8::try_from(character).ok()
+                .and_then(Self::get_tile_from_digit)
+                .ok_or(ParseError::InvalidCharacter { position: i, character })?;
+            let x_pos = i % 9;
+            let y_pos = i / 9;
+            if let Tile::Num(num) = tile {
+                // set_tile_at removes the digit from every peer, so an earlier copy shows up here
+                if !res.is_possible_at(x_pos, y_pos, num) {
+                    let first = res.find_peer_with(x_pos, y_pos, num).expect("a peer holds the digit");
+                    return Err(ParseError::DuplicateDigit { digit: num, first, second: i });
+                }
+                res.set_tile_at(x_pos, y_pos, tile);
+                res.clues += 1;
+            }
+        }
+        Ok(res)
+    }
+
+    // Candidate grids come in two forms, both in reading order:
+    //  - 729 characters, 9 per cell, where the k-th character is the digit k or '.'/'0' if it was eliminated
+    //  - 81 whitespace separated cells listing their candidates, e.g. "139"
+    // A cell with a single candidate is taken as placed
+    pub fn parse_candidates(input: &str) -> Result<Self, ParseError> {
+        let tokens = input.split_whitespace().collect::<Vec<_>>();
+        let mut masks = [[NONE_POSSIBLE; 9]; 9];
+        if tokens.len() == 81 {
+            let mut position = 0;
+            for (i, token) in tokens.iter().enumerate() {
+                for character in token.chars() {
+                    match character {
+                        '1'..='9' => masks[i % 9][i / 9] |= 1 << (character as usize - '1' as usize),
+                        '.' | '0' => {},
+                        _ => return Err(ParseError::InvalidCharacter { position, character }),
+                    }
+                    position += 1;
+                }
+            }
+        } else {
+            let compact = tokens.concat();
+            if compact.chars().count() != 729 {
+                return Err(ParseError::WrongCandidateCount(tokens.len()));
+            }
+            for (position, character) in compact.chars().enumerate() {
+                let i = position / 9;
+                let num = position % 9 + 1;
+                match character {
+                    '.' | '0' => {},
+                    _ if character.to_digit(10) == Some(num as u32) => masks[i % 9][i / 9] |= 1 << (num - 1),
+                    _ => return Err(ParseError::InvalidCharacter { position, character }),
+                }
+            }
+        }
+        Self::from_candidate_masks(masks)
+    }
+
+    // masks is indexed [x][y] like possible, cells with a single candidate are placed
+    fn from_candidate_masks(masks: [[u16; 9]; 9]) -> Result<Self, ParseError> {
+        let mut res = Self::new_blank();
+        for i in 0..81 {
+            let x_pos = i % 9;
+            let y_pos = i / 9;
+            let mask = masks[x_pos][y_pos];
+            if mask.count_ones() != 1 {
+                continue;
+            }
+            let num = mask.trailing_zeros() as usize + 1;
+            if res.board[x_pos][y_pos] == Tile::Void && !res.is_possible_at(x_pos, y_pos, num) {
+                if let Some(first) = res.find_peer_with(x_pos, y_pos, num) {
+                    return Err(ParseError::DuplicateDigit { digit: num, first, second: i });
+                }
+            }
+            res.set_tile_at(x_pos, y_pos, Tile::Num(num));
+            res.clues += 1;
+        }
+        for (possible, mask) in res.possible.iter_mut().flatten().zip(masks.iter().flatten()) {
+            *possible &= mask;
+        }
+        res.recompute_unit_possibilities();
+        Ok(res)
+    }
+
+    // Reads a https://www.sudokuwiki.org/sudoku.htm?bd=... link. The board is either the 81 digits
+    // written by sudoku_wiki_link, or 162 characters giving each cell's candidate bitmask as two
+    // base 32 digits (0-9, a-v), as written by sudoku_wiki_candidate_link
+    pub fn from_sudoku_wiki_link(url: &str) -> Result<Self, ParseError> {
+        let query = url.trim().split_once('?').map(|(_, query)| query).unwrap_or_default();
+        let query = query.split('#').next().unwrap_or_default();
+        let board = query.split('&')
+            .find_map(|parameter| parameter.strip_prefix("bd="))
+            .ok_or(ParseError::MissingBoard)?;
+        match board.chars().count() {
+            81 => Self::parse_line(board),
+            162 => {
+                let mut masks = [[NONE_POSSIBLE; 9]; 9];
+                let digits = board.chars().map(|c| c.to_digit(32)).collect::<Vec<_>>();
+                for (i, pair) in digits.chunks(2).enumerate() {
+                    let (Some(high), Some(low)) = (pair[0], pair[1]) else {
+                        let position = 2 * i + if pair[0].is_none() { 0 } else { 1 };
+                        let character = board.chars().nth(position).expect("position is in the board");
+                        return Err(ParseError::InvalidCharacter { position, character });
+                    };
+                    masks[i % 9][i / 9] = (high * 32 + low) as u16 & ALL_POSSIBLE;
+                }
+                Self::from_candidate_masks(masks)
+            },
+            len => Err(ParseError::WrongLength(len)),
+        }
+    }
+
+    fn find_peer_with(&self, x_pos: usize, y_pos: usize, num: usize) -> Option<usize> {
+        Unit::containing(x_pos, y_pos).iter()
+            .flat_map(|unit| unit.cells())
+            .find(|&(x, y)| (x, y) != (x_pos, y_pos) && self.board[x][y] == Tile::Num(num))
+            .map(|(x, y)| y * 9 + x)
+    }
+}
+
+impl FromStr for Sudoku {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn parses_valid_puzzle() {
+        let sudoku = Sudoku::parse(PUZZLE).unwrap();
+        assert_eq!(sudoku.clues, 26);
+        assert_eq!(sudoku.board[0][0], Tile::Num(2));
+        assert_eq!(sudoku.board[1][0], Tile::Void);
+        assert_eq!(sudoku, Sudoku::from_string(PUZZLE));
+    }
+
+    #[test]
+    fn dots_and_zeros_are_empty() {
+        let dotted = PUZZLE.replace('0', ".");
+        assert_eq!(dotted.parse::<Sudoku>(), Sudoku::parse(PUZZLE));
+        assert_eq!(Sudoku::parse(&format!("{}\n", dotted)), Sudoku::parse(PUZZLE));
+    }
+
+    #[test]
+    fn rejects_wrong_length() {
+        assert_eq!(Sudoku::parse(&PUZZLE[1..]), Err(ParseError::WrongLength(80)));
+        assert_eq!(Sudoku::parse(""), Err(ParseError::WrongLength(0)));
+    }
+
+    #[test]
+    fn rejects_invalid_character() {
+        let mut input = PUZZLE.to_string();
+        input.replace_range(5..6, "x");
+        assert_eq!(Sudoku::parse(&input), Err(ParseError::InvalidCharacter { position: 5, character: 'x' }));
+    }
+
+    #[test]
+    fn rejects_duplicate_digit() {
+        assert_eq!(Sudoku::parse(DUPLICATE_TWO), Err(ParseError::DuplicateDigit { digit: 2, first: 0, second: 1 }));
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let sudoku = Sudoku::parse(PUZZLE).unwrap();
+        assert_eq!(Sudoku::parse(&format!("{}", sudoku)), Ok(sudoku));
+    }
+
+    #[test]
+    fn parses_forum_grids() {
+        let boxed = "\
+            +-------+-------+-------+
+            | 2 . . | . 7 . | . 3 8 |
+            | . . . | . . 6 | . 7 . |
+            | 3 . . | . 4 . | 6 . . |
+            +-------+-------+-------+
+            | . . 8 | . 2 . | 7 . . |
+            | 1 . . | . . . | . . 6 |
+            | . . 7 | . 3 . | 4 . . |
+            +-------+-------+-------+
+            | . . 4 | . 8 . | . . 9 |
+            | . 6 . | 4 . . | . . . |
+            | 9 1 . | . 6 . | . . 2 |
+            +-------+-------+-------+";
+        assert_eq!(Sudoku::parse(boxed), Sudoku::parse(PUZZLE));
+
+        let plain = PUZZLE.as_bytes().chunks(9).map(|row| std::str::from_utf8(row).unwrap()).collect::<Vec<_>>().join("\r\n");
+        assert_eq!(Sudoku::parse(&plain), Sudoku::parse(PUZZLE));
+
+        // a row of nothing but empty cells is still a row, whatever marks them
+        let blank_row = format!("{}{}{}", &PUZZLE[..36], "0".repeat(9), &PUZZLE[45..]);
+        let starred = blank_row.replace('0', "*").as_bytes().chunks(9).map(|row| std::str::from_utf8(row).unwrap()).collect::<Vec<_>>().join("\n");
+        assert_eq!(Sudoku::parse(&starred), Sudoku::parse(&blank_row));
+        assert_eq!(Sudoku::parse(&starred.replace('*', "_")), Sudoku::parse(&blank_row));
+    }
+
+    #[test]
+    fn grid_errors() {
+        assert_eq!(Sudoku::parse("123\n456\n"), Err(ParseError::WrongLength(6)));
+        assert_eq!(Sudoku::parse("|1 2|\n|3 x|\n"), Err(ParseError::InvalidCharacter { position: 9, character: 'x' }));
+    }
+
+    #[test]
+    fn candidate_round_trip() {
+        let mut sudoku = Sudoku::parse(PUZZLE).unwrap();
+        // eliminate a candidate from the first cell that keeps at least two
+        let (x, y) = (0..81).map(|i| (i % 9, i / 9)).find(|&(x, y)| sudoku.possible[x][y].count_ones() >= 3).unwrap();
+        let num = sudoku.possible[x][y].trailing_zeros() as usize + 1;
+        sudoku.remove_possible_at(x, y, num);
+
+        let compact = sudoku.to_candidate_string();
+        assert_eq!(compact.len(), 729);
+        assert_eq!(&compact[..9], ".2.......");
+        assert_eq!(Sudoku::parse_candidates(&compact), Ok(sudoku));
+        assert_eq!(Sudoku::parse_candidates(&sudoku.to_candidate_grid()), Ok(sudoku));
+    }
+
+    #[test]
+    fn candidate_grid_keeps_units_consistent() {
+        let mut cells = vec!["123456789"; 81];
+        cells[0] = "5";
+        cells[1] = "12";
+        let sudoku = Sudoku::parse_candidates(&cells.join(" ")).unwrap();
+        assert_eq!(sudoku.board[0][0], Tile::Num(5));
+        assert_eq!(sudoku.clues, 1);
+        assert_eq!(sudoku.possible[1][0], 0b11);
+        // placing the 5 removes it from the row, and 3 was never a candidate in the second cell
+        assert_eq!(sudoku.row_possible[0][5 - 1], 0);
+        assert_eq!(sudoku.row_possible[0][3 - 1], 0b111111100);
+        assert_eq!(sudoku.box_possible[0][3 - 1], 0b111111100);
+    }
+
+    #[test]
+    fn candidate_errors() {
+        assert_eq!(Sudoku::parse_candidates("1 2 3"), Err(ParseError::WrongCandidateCount(3)));
+        let mut compact = "123456789".repeat(81);
+        compact.replace_range(3..4, "5");
+        assert_eq!(Sudoku::parse_candidates(&compact), Err(ParseError::InvalidCharacter { position: 3, character: '5' }));
+        let mut cells = vec!["123456789"; 81];
+        cells[0] = "5";
+        cells[8] = "5";
+        assert_eq!(Sudoku::parse_candidates(&cells.join(" ")), Err(ParseError::DuplicateDigit { digit: 5, first: 0, second: 8 }));
+    }
+
+    #[test]
+    fn sudoku_wiki_links() {
+        let sudoku = Sudoku::parse(PUZZLE).unwrap();
+        let link = sudoku.sudoku_wiki_link();
+        assert_eq!(link, format!("https://www.sudokuwiki.org/sudoku.htm?bd={}", PUZZLE));
+        assert_eq!(Sudoku::from_sudoku_wiki_link(&link), Ok(sudoku));
+        assert_eq!(Sudoku::from_sudoku_wiki_link(&format!("https://sudokuwiki.org/sudoku.htm?x=1&bd={}#top", PUZZLE)), Ok(sudoku));
+
+        let mut eliminated = sudoku;
+        eliminated.remove_possible_at(1, 0, 4);
+        let link = eliminated.sudoku_wiki_candidate_link();
+        assert_eq!(link.len(), "https://www.sudokuwiki.org/sudoku.htm?bd=".len() + 162);
+        assert_eq!(Sudoku::from_sudoku_wiki_link(&link), Ok(eliminated));
+    }
+
+    #[test]
+    fn sudoku_wiki_link_errors() {
+        assert_eq!(Sudoku::from_sudoku_wiki_link("https://www.sudokuwiki.org/sudoku.htm"), Err(ParseError::MissingBoard));
+        assert_eq!(Sudoku::from_sudoku_wiki_link("https://www.sudokuwiki.org/sudoku.htm?bd=123"), Err(ParseError::WrongLength(3)));
+        let bad = format!("https://www.sudokuwiki.org/sudoku.htm?bd=w{}", "0".repeat(161));
+        assert_eq!(Sudoku::from_sudoku_wiki_link(&bad), Err(ParseError::InvalidCharacter { position: 0, character: 'w' }));
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_string_panics_on_bad_input() {
+        Sudoku::from_string("123");
+    }
+}
@@ -1,20 +1,87 @@
 use crate::sudoku::*;
 
+const SUDOKU_WIKI_BASE: &str = "https://www.sudokuwiki.org/sudoku.htm?bd=";
+
 impl Sudoku {
-    pub fn print_sudoku_wiki_link(&mut self) {
-        let base = "https://www.sudokuwiki.org/sudoku.htm?bd=";
-        print!("{}", base);
+    // single line form understood by Sudoku::parse, with '.' for empty cells
+    pub fn to_digit_string(&self) -> String {
+        let mut res = String::with_capacity(81);
+        for y in 0..9 {
+            for x in 0..9 {
+                res.push_str(&self.board[x][y].to_string());
+            }
+        }
+        res
+    }
+
+    // 9 characters per cell, the digit if it is still a candidate (or placed) and '.' otherwise
+    pub fn to_candidate_string(&self) -> String {
+        let mut res = String::with_capacity(729);
+        for y in 0..9 {
+            for x in 0..9 {
+                for num in 1..=9 {
+                    if self.board[x][y] == Tile::Num(num) || self.possible[x][y] & (1 << (num - 1)) != 0 {
+                        res.push_str(&num.to_string());
+                    } else {
+                        res.push('.');
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    // One line per row of space separated cells, each listing its candidates (or placed digit)
+    pub fn to_candidate_grid(&self) -> String {
+        let mut rows = vec![];
+        for y in 0..9 {
+            let mut cells = vec![];
+            for x in 0..9 {
+                let cell = match self.board[x][y] {
+                    Tile::Num(num) => num.to_string(),
+                    Tile::Void => (1..=9).filter(|num| self.possible[x][y] & (1 << (num - 1)) != 0).map(|num| num.to_string()).collect(),
+                };
+                cells.push(if cell.is_empty() { ".".to_string() } else { cell });
+            }
+            rows.push(cells.join(" "));
+        }
+        rows.join("\n")
+    }
+
+    pub fn sudoku_wiki_link(&self) -> String {
+        let mut res = String::from(SUDOKU_WIKI_BASE);
         for y in 0..9 {
             for x in 0..9 {
                 match self.board[x][y] {
-                    Tile::Void => { print!("0") },
-                    Tile::Num(x) => { print!("{}", x) },
+                    Tile::Void => { res.push('0') },
+                    Tile::Num(x) => { res.push_str(&x.to_string()) },
                 }
             }
         }
-        println!();
+        res
+    }
+
+    // Keeps the current candidates: each cell is its candidate bitmask as two base 32 digits
+    pub fn sudoku_wiki_candidate_link(&self) -> String {
+        let mut res = String::from(SUDOKU_WIKI_BASE);
+        for y in 0..9 {
+            for x in 0..9 {
+                let mask = match self.board[x][y] {
+                    Tile::Void => self.possible[x][y] as u32,
+                    Tile::Num(num) => 1 << (num - 1),
+                };
+                res.push(char::from_digit(mask / 32, 32).expect("mask fits in 10 bits"));
+                res.push(char::from_digit(mask % 32, 32).expect("digit is below 32"));
+            }
+        }
+        res
+    }
+
+    pub fn print_sudoku_wiki_link(&mut self) {
+        println!("{}", self.sudoku_wiki_link());
     }
 
+    #[allow(unused_variables)]
     pub fn print_with_possibilities(&mut self) {
         println!("-------------------------------------");
         for j in 0..9 {
@@ -0,0 +1,131 @@
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use crate::sudoku::*;
+
+// Tiles are a single number, 0 for an empty cell
+impl Serialize for Tile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Tile::Void => serializer.serialize_u8(0),
+            Tile::Num(num) => serializer.serialize_u8(*num as u8),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Tile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match u8::deserialize(deserializer)? {
+            0 => Ok(Tile::Void),
+            num @ 1..=9 => Ok(Tile::Num(num.into())),
+            num => Err(de::Error::custom(format!("tile must be between 0 and 9, found {}", num))),
+        }
+    }
+}
+
+// A Sudoku is stored as its 81 character grid, plus the 729 character candidate
+// string only when the candidates differ from what the grid alone gives.
+// The clue count is recomputed from the grid when reading back.
+#[derive(Serialize, Deserialize)]
+struct CompactSudoku {
+    grid: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    candidates: Option<String>,
+}
+
+// Plain puzzle strings are accepted as well as the object form
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SudokuInput {
+    Grid(String),
+    Compact(CompactSudoku),
+}
+
+impl Serialize for Sudoku {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let grid = self.to_digit_string();
+        let from_grid = Sudoku::parse(&grid).map_err(serde::ser::Error::custom)?;
+        let candidates = if from_grid.possible == self.possible {
+            None
+        } else {
+            Some(self.to_candidate_string())
+        };
+        CompactSudoku { grid, candidates }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Sudoku {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let compact = match SudokuInput::deserialize(deserializer)? {
+            SudokuInput::Grid(grid) => CompactSudoku { grid, candidates: None },
+            SudokuInput::Compact(compact) => compact,
+        };
+        let mut res = Sudoku::parse(&compact.grid).map_err(de::Error::custom)?;
+        if let Some(candidates) = compact.candidates {
+            // the board comes from the grid, the candidates can only narrow what it allows
+            let from_candidates = Sudoku::parse_candidates(&candidates).map_err(de::Error::custom)?;
+            for x in 0..9 {
+                for y in 0..9 {
+                    res.possible[x][y] &= match from_candidates.board[x][y] {
+                        Tile::Num(num) => 1 << (num - 1),
+                        Tile::Void => from_candidates.possible[x][y],
+                    };
+                }
+            }
+            res.recompute_unit_possibilities();
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn sudoku_is_compact() {
+        let sudoku = Sudoku::parse(PUZZLE).unwrap();
+        let json = serde_json::to_string(&sudoku).unwrap();
+        assert_eq!(json, format!("{{\"grid\":\"{}\"}}", PUZZLE.replace('0', ".")));
+        assert_eq!(serde_json::from_str::<Sudoku>(&json).unwrap(), sudoku);
+        assert_eq!(serde_json::from_str::<Sudoku>(&format!("\"{}\"", PUZZLE)).unwrap(), sudoku);
+    }
+
+    #[test]
+    fn sudoku_keeps_candidates() {
+        let mut sudoku = Sudoku::parse(PUZZLE).unwrap();
+        sudoku.remove_possible_at(1, 0, 4);
+        let json = serde_json::to_value(sudoku).unwrap();
+        assert_eq!(json["candidates"].as_str().map(str::len), Some(729));
+        assert_eq!(serde_json::from_value::<Sudoku>(json).unwrap(), sudoku);
+    }
+
+    #[test]
+    fn tiles_are_numbers() {
+        assert_eq!(serde_json::to_string(&[Tile::Void, Tile::Num(7)]).unwrap(), "[0,7]");
+        assert_eq!(serde_json::from_str::<Tile>("9").unwrap(), Tile::Num(9));
+        assert!(serde_json::from_str::<Tile>("10").is_err());
+    }
+
+    #[test]
+    fn solution_count_and_difficulty_shapes() {
+        assert_eq!(serde_json::to_string(&SolutionCount::Zero).unwrap(), r#"{"status":"zero"}"#);
+        let one = SolutionCount::One(Sudoku::parse(PUZZLE).unwrap());
+        let json = serde_json::to_value(one).unwrap();
+        assert_eq!(json["status"], "one");
+        assert_eq!(json["solution"]["grid"].as_str().map(str::len), Some(81));
+        assert_eq!(serde_json::from_value::<SolutionCount>(json).unwrap(), one);
+        let multiple = SolutionCount::Multiple(Sudoku::parse(PUZZLE).unwrap(), Sudoku::new_blank());
+        let json = serde_json::to_value(multiple).unwrap();
+        assert_eq!(json["status"], "multiple");
+        assert_eq!(json["solution"].as_array().map(Vec::len), Some(2));
+        assert_eq!(serde_json::from_value::<SolutionCount>(json).unwrap(), multiple);
+
+        assert_eq!(serde_json::to_string(&Difficulty::Trivial).unwrap(), r#"{"level":"trivial"}"#);
+        assert_eq!(serde_json::to_string(&Difficulty::LevelTwo(4)).unwrap(), r#"{"level":"level_two","count":4}"#);
+        let difficulty = serde_json::from_str::<Difficulty>(r#"{"level":"too_deep"}"#).unwrap();
+        assert_eq!(difficulty, Difficulty::TooDeep);
+    }
+}
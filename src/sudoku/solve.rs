@@ -1,4 +1,23 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
 use crate::sudoku::*;
+use crate::sudoku::stats::*;
+
+// puzzles handed to a worker at a time; small enough that one slow puzzle doesn't stall a thread's whole share
+const BATCH_CHUNK: usize = 64;
+
+enum Propagation {
+    Contradiction,
+    Complete,
+    Stuck,
+}
+
+// Every subset of set with exactly size bits, walking down from set itself
+fn subsets(set: u16, size: usize) -> impl Iterator<Item = u16> {
+    std::iter::successors(Some(set), move |&subset| (subset != 0).then(|| (subset - 1) & set))
+        .filter(move |subset| subset.count_ones() as usize == size)
+}
 
 impl Sudoku {
     fn get_naked_single(&mut self, x_pos: usize, y_pos: usize) ->  (Tile, bool) {
@@ -14,6 +33,7 @@ impl Sudoku {
         }
         (res, res == Tile::Void)
     }
+    #[cfg(test)]
     fn get_naked_pair(&mut self, x_pos: usize, y_pos: usize) -> (Tile, Tile) {
         let mut first = Tile::Void;
         let mut second = Tile::Void;
@@ -29,7 +49,7 @@ impl Sudoku {
         }
         (first, second)
     }
-    fn get_best_guess_spot(&mut self, debug: bool) -> (usize, usize) {
+    fn get_best_guess_spot(&mut self, _debug: bool) -> (usize, usize) {
         let mut best_x = 0;
         let mut best_y = 0;
         let mut min_possible = usize::MAX;
@@ -179,51 +199,45 @@ impl Sudoku {
         changed
     }
 
-    fn apply_hidden_pairs_columns(&mut self, debug: bool) -> bool {
+    // Hidden subsets: if `size` digits can only go in the same `size` cells of a unit,
+    // those cells can't hold any other digit. units is Unit::Row, Unit::Column or Unit::Box
+    fn apply_hidden_subsets(&mut self, units: fn(usize) -> Unit, size: usize, debug: bool) -> bool {
         let mut changed = false;
-        for x in 0..9 {
-            for num_first in 1..=8 {
-                for num_second in (num_first + 1)..=9 {
-                    if self.column_possible[x][num_first - 1] == self.column_possible[x][num_second - 1] {
-                        // test if only two possibilities
-                        let mut possible = self.column_possible[x][num_first - 1];
-                        let mut first_y = None;
-                        let mut second_y = None;
-                        for y in 0..9 {
-                            if possible % 2 == 0b1 {
-                                if first_y == None {
-                                    first_y = Some(y);
-                                } else if second_y == None {
-                                    second_y = Some(y);
-                                } else {
-                                    first_y = None;
-                                    second_y = None;
-                                    break;
-                                }
-                            }
-                            possible >>= 1;
-                        }
-                        if let Some(y0) = first_y {
-                            if let Some(y1) = second_y {
-                                // Remove other possibilities
-                                for val in 1..=9 {
-                                    if val == num_first || val == num_second {
-                                        continue;
-                                    }
-                                    let old_y0 = self.possible[x][y0];
-                                    let old_y1 = self.possible[x][y1];
-                                    self.remove_possible_at(x, y0, val);
-                                    self.remove_possible_at(x, y1, val);
-                                    if old_y0 != self.possible[x][y0] || old_y1 != self.possible[x][y1] {
-                                        changed = true;
-                                    }
-                                }
-                            }
+        for index in 0..9 {
+            let unit = units(index);
+            let cells = unit.cells();
+            // positions[val - 1] has bit k set when the unit's k-th cell can hold val
+            let mut positions = [NONE_POSSIBLE; 9];
+            let mut candidates = NONE_POSSIBLE;
+            for val in 1..=9 {
+                positions[val - 1] = self.unit_possible(unit, val);
+                let count = positions[val - 1].count_ones() as usize;
+                if count >= 1 && count <= size {
+                    candidates |= 1 << (val - 1);
+                }
+            }
+            for digits in subsets(candidates, size) {
+                let mut subset_cells = NONE_POSSIBLE;
+                for val in 1..=9 {
+                    if digits & (1 << (val - 1)) != 0 {
+                        subset_cells |= positions[val - 1];
+                    }
+                }
+                if subset_cells.count_ones() as usize != size {
+                    continue;
+                }
+                for (k, &(x, y)) in cells.iter().enumerate() {
+                    if subset_cells & (1 << k) == 0 || self.possible[x][y] & !digits == 0 {
+                        continue;
+                    }
+                    for val in 1..=9 {
+                        if digits & (1 << (val - 1)) == 0 {
+                            self.remove_possible_at(x, y, val);
                         }
-
-                        // if three are the same, we know that there can't just be two possibilities
-                        // therefore we can break to save time
-                        break;
+                    }
+                    changed = true;
+                    if debug {
+                        println!("hidden subset of {} in {} ({}, {})", size, unit, x, y);
                     }
                 }
             }
@@ -231,51 +245,55 @@ impl Sudoku {
         changed
     }
 
+    #[cfg(test)]
     fn apply_hidden_pairs_rows(&mut self, debug: bool) -> bool {
+        self.apply_hidden_subsets(Unit::Row, 2, debug)
+    }
+
+    #[cfg(test)]
+    fn apply_hidden_trips_rows(&mut self, debug: bool) -> bool {
+        self.apply_hidden_subsets(Unit::Row, 3, debug)
+    }
+
+    // Pointing: if a box's candidates for a digit all lie in one row (or column),
+    // the digit must go there, so remove it from the rest of that row (or column)
+    fn apply_pointing(&mut self, debug: bool) -> bool {
         let mut changed = false;
-        for y in 0..9 {
-            for num_first in 1..=8 {
-                for num_second in (num_first + 1)..=9 {
-                    if self.row_possible[y][num_first - 1] == self.row_possible[y][num_second - 1] {
-                        // test if only two possibilities
-                        let mut possible = self.row_possible[y][num_first - 1];
-                        let mut first_x = None;
-                        let mut second_x = None;
-                        for x in 0..9 {
-                            if possible % 2 == 0b1 {
-                                if first_x == None {
-                                    first_x = Some(x);
-                                } else if second_x == None {
-                                    second_x = Some(x);
-                                } else {
-                                    first_x = None;
-                                    second_x = None;
-                                    break;
+        for box_index in 0..9 {
+            let box_x = 3 * (box_index % 3);
+            let box_y = 3 * (box_index / 3);
+            for val in 1..=9 {
+                // index_in_box is dx + 3 * dy
+                let box_possible = self.box_possible[box_index][val - 1];
+                if box_possible == 0 {
+                    continue;
+                }
+                for i in 0..3 {
+                    let row_mask = 0b000000111 << (3 * i);
+                    let column_mask = 0b001001001 << i;
+                    if box_possible & !row_mask == 0 {
+                        let y = box_y + i;
+                        for x in (0..9).filter(|x| x / 3 != box_x / 3) {
+                            if self.is_possible_at(x, y, val) {
+                                self.remove_possible_at(x, y, val);
+                                changed = true;
+                                if debug {
+                                    println!("pointing {} in row ({}, {})", val, x, y);
                                 }
                             }
-                            possible >>= 1;
                         }
-                        if let Some(x0) = first_x {
-                            if let Some(x1) = second_x {
-                                // Remove other possibilities
-                                for val in 1..=9 {
-                                    if val == num_first || val == num_second {
-                                        continue;
-                                    }
-                                    let old_x0 = self.possible[x0][y];
-                                    let old_x1 = self.possible[x1][y];
-                                    self.remove_possible_at(x0, y, val);
-                                    self.remove_possible_at(x1, y, val);
-                                    if old_x0 != self.possible[x0][y] || old_x1 != self.possible[x1][y] {
-                                        changed = true;
-                                    }
+                    }
+                    if box_possible & !column_mask == 0 {
+                        let x = box_x + i;
+                        for y in (0..9).filter(|y| y / 3 != box_y / 3) {
+                            if self.is_possible_at(x, y, val) {
+                                self.remove_possible_at(x, y, val);
+                                changed = true;
+                                if debug {
+                                    println!("pointing {} in column ({}, {})", val, x, y);
                                 }
                             }
                         }
-
-                        // if three are the same, we know that there can't just be two possibilities
-                        // therefore we can break to save time
-                        break;
                     }
                 }
             }
@@ -283,58 +301,39 @@ impl Sudoku {
         changed
     }
 
-    fn apply_hidden_pairs_boxes(&mut self, debug: bool) -> bool {
+    // Claiming: if a row's (or column's) candidates for a digit all lie in one box,
+    // the digit must go there, so remove it from the rest of that box
+    fn apply_claiming(&mut self, debug: bool) -> bool {
         let mut changed = false;
-        for box_num in 0..9 {
-            for num_first in 1..=8 {
-                for num_second in (num_first + 1)..=9 {
-                    if self.box_possible[box_num][num_first - 1] == self.box_possible[box_num][num_second - 1] {
-                        // test if only two possibilities
-                        let mut possible = self.box_possible[box_num][num_first - 1];
-                        let mut first_i = None;
-                        let mut second_i = None;
-                        for box_i in 0..9 {
-                            if possible % 2 == 0b1 {
-                                if first_i == None {
-                                    first_i = Some(box_i);
-                                } else if second_i == None {
-                                    second_i = Some(box_i);
-                                } else {
-                                    first_i = None;
-                                    second_i = None;
-                                    break;
+        for line in 0..9 {
+            for val in 1..=9 {
+                let row_possible = self.row_possible[line][val - 1];
+                let column_possible = self.column_possible[line][val - 1];
+                for third in 0..3 {
+                    let third_mask = 0b111 << (3 * third);
+                    // the row's band and the third pick out the box
+                    if row_possible != 0 && row_possible & !third_mask == 0 {
+                        let box_y = line - line % 3;
+                        for (x, y) in Unit::Box(third + box_y).cells() {
+                            if y != line && self.is_possible_at(x, y, val) {
+                                self.remove_possible_at(x, y, val);
+                                changed = true;
+                                if debug {
+                                    println!("claiming {} from row ({}, {})", val, x, y);
                                 }
                             }
-                            possible >>= 1;
                         }
-                        if let Some(i0) = first_i {
-                            if let Some(i1) = second_i {
-                                // Remove other possibilities
-                                for val in 1..=9 {
-                                    if val == num_first || val == num_second {
-                                        continue;
-                                    }
-                                    let box_x = 3 * (box_num % 3);
-                                    let box_y = 3 * (box_num / 3);
-                                    let x0 = box_x + i0 % 3;
-                                    let y0 = box_y + i0 / 3;
-                                    let x1 = box_x + i1 % 3;
-                                    let y1 = box_y + i1 / 3;
-
-                                    let old_i0 = self.possible[x0][y0];
-                                    let old_i1 = self.possible[x1][y1];
-                                    self.remove_possible_at(x0, y0, val);
-                                    self.remove_possible_at(x1, y1, val);
-                                    if old_i0 != self.possible[x0][y0] || old_i1 != self.possible[x1][y1] {
-                                        changed = true;
-                                    }
+                    }
+                    if column_possible != 0 && column_possible & !third_mask == 0 {
+                        for (x, y) in Unit::Box(line / 3 + 3 * third).cells() {
+                            if x != line && self.is_possible_at(x, y, val) {
+                                self.remove_possible_at(x, y, val);
+                                changed = true;
+                                if debug {
+                                    println!("claiming {} from column ({}, {})", val, x, y);
                                 }
                             }
                         }
-
-                        // if three are the same, we know that there can't just be two possibilities
-                        // therefore we can break to save time
-                        break;
                     }
                 }
             }
@@ -342,227 +341,99 @@ impl Sudoku {
         changed
     }
 
-    fn apply_hidden_trips_columns(&mut self, debug: bool) -> bool {
+    #[cfg(test)]
+    fn apply_naked_pairs(&mut self, debug: bool) -> bool {
         let mut changed = false;
-        for x in 0..9 {
-            for num_first in 1..=7 {
-                let first_poss = self.column_possible[x][num_first - 1];
-                if first_poss == NONE_POSSIBLE {
-                    continue;
-                }
-                for num_second in (num_first + 1)..=8 {
-                    let second_poss = self.column_possible[x][num_second - 1];
-                    if second_poss == NONE_POSSIBLE {
-                        continue;
-                    }
-                    for num_third in (num_second + 1)..=9 {
-                        let third_poss = self.column_possible[x][num_third - 1];
-                        if third_poss == NONE_POSSIBLE {
-                            continue;
-                        }
-                        let mut intersection = first_poss | second_poss | third_poss;
-
-                        let mut first_y = None;
-                        let mut second_y = None;
-                        let mut third_y = None;
-                        for y in 0..9 {
-                            if intersection % 2 == 0b1 {
-                                if first_y == None {
-                                    first_y = Some(y);
-                                } else if second_y == None {
-                                    second_y = Some(y);
-                                } else if third_y == None {
-                                    third_y = Some(y);
-                                } else {
-                                    first_y = None;
-                                    second_y = None;
-                                    third_y = None;
-                                    break;
-                                }
-                            }
-                            intersection >>= 1;
-                        }
-                        if let Some(y0) = first_y {
-                            if let Some(y1) = second_y {
-                                if let Some(y2) = third_y {
-                                    // Remove other possibilities
-                                    let old_y0 = self.possible[x][y0];
-                                    let old_y1 = self.possible[x][y1];
-                                    let old_y2 = self.possible[x][y2];
-
-                                    for val in 1..=9 {
-                                        if val == num_first || val == num_second || val == num_third {
-                                            continue;
-                                        }
-                                        self.remove_possible_at(x, y0, val);
-                                        self.remove_possible_at(x, y1, val);
-                                        self.remove_possible_at(x, y2, val);
-                                    }
-                                    if old_y0 != self.possible[x][y0] || old_y1 != self.possible[x][y1] || old_y2 != self.possible[x][y2] {
-                                        changed = true;
-                                        if debug {
-                                            println!("trips in column: {}", x);
-                                            self.print_with_possibilities();
-                                            println!();
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+        for units in [Unit::Column, Unit::Row, Unit::Box] {
+            changed |= self.apply_naked_subsets(units, 2, debug);
         }
         changed
     }
 
-    fn apply_hidden_trips_rows(&mut self, debug: bool) -> bool {
+    // Naked subsets: if `size` empty cells of a unit only have `size` digits between them
+    // (each cell holding any of them, e.g. 12, 23 and 13 for a triple), those digits
+    // can be removed from every other cell in the unit. units is Unit::Row, Unit::Column or Unit::Box
+    fn apply_naked_subsets(&mut self, units: fn(usize) -> Unit, size: usize, debug: bool) -> bool {
         let mut changed = false;
-        for y in 0..9 {
-            for num_first in 1..=7 {
-                let first_poss = self.row_possible[y][num_first - 1];
-                if first_poss == NONE_POSSIBLE {
+        for index in 0..9 {
+            let unit = units(index);
+            let cells = unit.cells();
+            // bit k is set when the unit's k-th cell could be part of a subset
+            let mut candidates = 0u16;
+            for (k, &(x, y)) in cells.iter().enumerate() {
+                let count = self.possible[x][y].count_ones() as usize;
+                if self.board[x][y] == Tile::Void && count >= 1 && count <= size {
+                    candidates |= 1 << k;
+                }
+            }
+            for subset in subsets(candidates, size) {
+                let mut digits = NONE_POSSIBLE;
+                for (k, &(x, y)) in cells.iter().enumerate() {
+                    if subset & (1 << k) != 0 {
+                        digits |= self.possible[x][y];
+                    }
+                }
+                if digits.count_ones() as usize != size {
                     continue;
                 }
-                for num_second in (num_first + 1)..=8 {
-                    let second_poss = self.row_possible[y][num_second - 1];
-                    if second_poss == NONE_POSSIBLE {
+                for (k, &(x, y)) in cells.iter().enumerate() {
+                    if subset & (1 << k) != 0 || self.possible[x][y] & digits == 0 {
                         continue;
                     }
-                    for num_third in (num_second + 1)..=9 {
-                        let third_poss = self.row_possible[y][num_third - 1];
-                        if third_poss == NONE_POSSIBLE {
-                            continue;
-                        }
-                        let mut intersection = first_poss | second_poss | third_poss;
-
-                        let mut first_x = None;
-                        let mut second_x = None;
-                        let mut third_x = None;
-                        for x in 0..9 {
-                            if intersection % 2 == 0b1 {
-                                if first_x == None {
-                                    first_x = Some(x);
-                                } else if second_x == None {
-                                    second_x = Some(x);
-                                } else if third_x == None {
-                                    third_x = Some(x);
-                                } else {
-                                    first_x = None;
-                                    second_x = None;
-                                    third_x = None;
-                                    break;
-                                }
-                            }
-                            intersection >>= 1;
-                        }
-                        if let Some(x0) = first_x {
-                            if let Some(x1) = second_x {
-                                if let Some(x2) = third_x {
-                                    // Remove other possibilities
-                                    let old_x0 = self.possible[x0][y];
-                                    let old_x1 = self.possible[x1][y];
-                                    let old_x2 = self.possible[x2][y];
-
-                                    for val in 1..=9 {
-                                        if val == num_first || val == num_second || val == num_third {
-                                            continue;
-                                        }
-                                        self.remove_possible_at(x0, y, val);
-                                        self.remove_possible_at(x1, y, val);
-                                        self.remove_possible_at(x2, y, val);
-                                    }
-                                    if old_x0 != self.possible[x0][y] || old_x1 != self.possible[x1][y] || old_x2 != self.possible[x2][y] {
-                                        changed = true;
-                                        if debug {
-                                            println!("trips in row: {}", y);
-                                            self.print_with_possibilities();
-                                            println!();
-                                        }
-                                    }
-                                }
-                            }
+                    for val in 1..=9 {
+                        if digits & (1 << (val - 1)) != 0 {
+                            self.remove_possible_at(x, y, val);
                         }
                     }
+                    changed = true;
+                    if debug {
+                        println!("naked subset of {} in {} ({}, {})", size, unit, x, y);
+                    }
                 }
             }
         }
         changed
     }
 
-    fn apply_hidden_trips_boxes(&mut self, debug: bool) -> bool {
+    // Fish: if a digit's positions in `size` base lines (rows or columns) all fall in the
+    // same `size` cross lines, it must take those crossings, so it can be removed from the
+    // rest of the cross lines. X-Wing is size 2, Swordfish 3 and Jellyfish 4
+    fn apply_fish(&mut self, base: fn(usize) -> Unit, size: usize, debug: bool) -> bool {
+        let cross = match base(0) {
+            Unit::Row(_) => Unit::Column,
+            Unit::Column(_) => Unit::Row,
+            Unit::Box(_) => panic!("fish need rows or columns as base lines"),
+        };
         let mut changed = false;
-        for box_num in 0..9 {
-            for num_first in 1..=7 {
-                let first_poss = self.box_possible[box_num][num_first - 1];
-                if first_poss == NONE_POSSIBLE {
-                    continue;
+        for val in 1..=9 {
+            // positions[i] has bit j set when val can go where base line i meets cross line j
+            let mut positions = [NONE_POSSIBLE; 9];
+            let mut candidates = NONE_POSSIBLE;
+            for (i, position) in positions.iter_mut().enumerate() {
+                *position = self.unit_possible(base(i), val);
+                let count = position.count_ones() as usize;
+                if count >= 1 && count <= size {
+                    candidates |= 1 << i;
                 }
-                for num_second in (num_first + 1)..=8 {
-                    let second_poss = self.box_possible[box_num][num_second - 1];
-                    if second_poss == NONE_POSSIBLE {
-                        continue;
+            }
+            for lines in subsets(candidates, size) {
+                let mut cross_lines = NONE_POSSIBLE;
+                for (i, position) in positions.iter().enumerate() {
+                    if lines & (1 << i) != 0 {
+                        cross_lines |= position;
                     }
-                    for num_third in (num_second + 1)..=9 {
-                        let third_poss = self.box_possible[box_num][num_third - 1];
-                        if third_poss == NONE_POSSIBLE {
-                            continue;
-                        }
-                        let mut intersection = first_poss | second_poss | third_poss;
-
-                        let mut first_i = None;
-                        let mut second_i = None;
-                        let mut third_i = None;
-                        for box_i in 0..9 {
-                            if intersection % 2 == 0b1 {
-                                if first_i == None {
-                                    first_i = Some(box_i);
-                                } else if second_i == None {
-                                    second_i = Some(box_i);
-                                } else if third_i == None {
-                                    third_i = Some(box_i);
-                                } else {
-                                    first_i = None;
-                                    second_i = None;
-                                    third_i = None;
-                                    break;
-                                }
-                            }
-                            intersection >>= 1;
-                        }
-                        if let Some(i0) = first_i {
-                            if let Some(i1) = second_i {
-                                if let Some(i2) = third_i {
-                                    let box_x = 3 * (box_num % 3);
-                                    let box_y = 3 * (box_num / 3);
-                                    let x0 = box_x + i0 % 3;
-                                    let y0 = box_y + i0 / 3;
-                                    let x1 = box_x + i1 % 3;
-                                    let y1 = box_y + i1 / 3;
-                                    let x2 = box_x + i2 % 3;
-                                    let y2 = box_y + i2 / 3;
-                                    // Remove other possibilities
-                                    let old_x0 = self.possible[x0][y0];
-                                    let old_x1 = self.possible[x1][y2];
-                                    let old_x2 = self.possible[x2][y2];
-
-                                    for val in 1..=9 {
-                                        if val == num_first || val == num_second || val == num_third {
-                                            continue;
-                                        }
-                                        self.remove_possible_at(x0, y0, val);
-                                        self.remove_possible_at(x1, y1, val);
-                                        self.remove_possible_at(x2, y2, val);
-                                    }
-                                    if old_x0 != self.possible[x0][y0] || old_x1 != self.possible[x1][y1] || old_x2 != self.possible[x2][y2] {
-                                        changed = true;
-                                        if debug {
-                                            println!("trips in box: {} with nums: ({}, {}, {}) i: ({}, {}, {})", box_num, num_first, num_second, num_third, i0, i1, i2);
-                                            self.print_with_possibilities();
-                                            println!();
-                                        }
-                                    }
-                                }
+                }
+                if cross_lines.count_ones() as usize != size {
+                    continue;
+                }
+                for j in (0..9).filter(|j| cross_lines & (1 << j) != 0) {
+                    // the k-th cell of a cross line is on base line k
+                    for (k, (x, y)) in cross(j).cells().into_iter().enumerate() {
+                        if lines & (1 << k) == 0 && self.is_possible_at(x, y, val) {
+                            self.remove_possible_at(x, y, val);
+                            changed = true;
+                            if debug {
+                                println!("fish of {} removing {} from {} ({}, {})", size, val, cross(j), x, y);
                             }
                         }
                     }
@@ -572,192 +443,110 @@ impl Sudoku {
         changed
     }
 
-    fn apply_naked_pairs(&mut self, debug: bool) -> bool {
-        let mut changed = false;
-        let mut naked_pair_list: Vec<((Tile, Tile), usize, usize)> = vec![];
-        for x in 0..9 {
-            for y in 0..9 {
-                let naked_pair = self.get_naked_pair(x, y);
-                // If the pair is not void
-                if let Tile::Num(pair_a) = naked_pair.0 {
-                    if let Tile::Num(pair_b) = naked_pair.1 {
-                        for naked_pair_coords in &naked_pair_list {
-                            let other_pair = naked_pair_coords.0;
-                            let other_x = naked_pair_coords.1;
-                            let other_y = naked_pair_coords.2;
-                            if other_pair == naked_pair {
-                                // if in the same column
-                                if x == other_x {
-                                    for y_2 in 0..9 {
-                                        if y_2 == y || y_2 == other_y {
-                                            continue;
-                                        }
-                                        // remove the possibilities from others in the column
-                                        if self.is_possible_at(x, y_2, pair_a) || self.is_possible_at(x, y_2, pair_b) {
-                                            changed = true;
-                                        }
-                                        self.remove_possible_at(x, y_2, pair_a);
-                                        self.remove_possible_at(x, y_2, pair_b);
-                                    }
-                                }
-                                // if in the same row (we can use else as the coords are different)
-                                else if y == other_y {
-                                    for x_2 in 0..9 {
-                                        if x_2 == x || x_2 == other_x {
-                                            continue;
-                                        }
-                                        // remove the possibilities from others in the row
-                                        if self.is_possible_at(x_2, y, pair_a) || self.is_possible_at(x_2, y, pair_b) {
-                                            changed = true;
-                                        }
-                                        self.remove_possible_at(x_2, y, pair_a);
-                                        self.remove_possible_at(x_2, y, pair_b);
-                                        if debug && changed {
-                                            println!("naked pairing ({}, {})", x_2, y);
-                                            // self.print_with_possibilities();
-                                        }
-                                    }
-                                }
-                                // if in the same box
-                                if x / 3 == other_x / 3 && y / 3 == other_y / 3 {
-                                    let box_x = x - x % 3;
-                                    let box_y = y - y % 3;
-                                    for dx in 0..3 {
-                                        for dy in 0..3 {
-                                            let remove_x = box_x + dx;
-                                            let remove_y = box_y + dy;
-                                            if remove_x == x && remove_y == y {
-                                                continue;
-                                            }
-                                            if remove_x == other_x && remove_y == other_y {
-                                                continue;
-                                            }
-                                            // remove the possibilities from others in the box
-                                            if self.is_possible_at(remove_x, remove_y, pair_a) || self.is_possible_at(remove_x, remove_y, pair_b) {
-                                                changed = true;
-                                            }
-                                            self.remove_possible_at(remove_x, remove_y, pair_a);
-                                            self.remove_possible_at(remove_x, remove_y, pair_b);
-                                            if debug && changed {
-                                                println!("naked pairing ({}, {})", remove_x, remove_y);
-                                                // self.print_with_possibilities();
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }  // end for
-                        naked_pair_list.push((naked_pair, x, y));
-                    }
-                }
-            }
+    // One rung of the propagate ladder, over every unit (or base line) it applies to
+    fn apply_technique(&mut self, technique: Technique, debug: bool) -> bool {
+        let units = [Unit::Column, Unit::Row, Unit::Box];
+        let lines = [Unit::Row, Unit::Column];
+        match technique {
+            Technique::NakedSingle => self.fill_naked_singles(debug).0,
+            Technique::LastInColumn => self.fill_last_in_column(debug),
+            Technique::LastInRow => self.fill_last_in_row(debug),
+            Technique::LastInBox => self.fill_last_in_box(debug),
+            // pointing and claiming only read the unit masks, so they're cheap enough to try before the pairs
+            Technique::Pointing => self.apply_pointing(debug),
+            Technique::Claiming => self.apply_claiming(debug),
+            Technique::NakedPair => units.map(|units| self.apply_naked_subsets(units, 2, debug)).contains(&true),
+            Technique::NakedTriple => units.map(|units| self.apply_naked_subsets(units, 3, debug)).contains(&true),
+            Technique::HiddenPair => units.map(|units| self.apply_hidden_subsets(units, 2, debug)).contains(&true),
+            // hidden trips (unlikely to help much; improves 0-guess solve count from 34115 to 34242 (with col), to 34359 (with col and row), to 34393 (with col, row, box) out of 49151)
+            // slows down performance of even the hardest 17-tile puzzles
+            Technique::HiddenTriple => units.map(|units| self.apply_hidden_subsets(units, 3, debug)).contains(&true),
+            Technique::NakedQuad => units.map(|units| self.apply_naked_subsets(units, 4, debug)).contains(&true),
+            Technique::HiddenQuad => units.map(|units| self.apply_hidden_subsets(units, 4, debug)).contains(&true),
+            Technique::XWing => lines.map(|base| self.apply_fish(base, 2, debug)).contains(&true),
+            Technique::Swordfish => lines.map(|base| self.apply_fish(base, 3, debug)).contains(&true),
+            Technique::Jellyfish => lines.map(|base| self.apply_fish(base, 4, debug)).contains(&true),
+        }
+    }
+
+    // Runs one technique, crediting its placements and eliminations when stats are being kept
+    fn tracked<F>(&mut self, technique: Technique, stats: &mut Option<&mut SolveStats>, apply: F) -> bool
+    where F: FnOnce(&mut Self) -> bool {
+        let Some(stats) = stats else {
+            return apply(self);
+        };
+        let before = self.possible;
+        let changed = apply(self);
+        if changed {
+            stats.record(technique, &before, self);
         }
         changed
     }
 
-    pub fn solve_no_guessing(&mut self, only_basic: bool, iter_count: usize, debug: bool) -> SolutionCount {
+    // Applies the techniques until nothing changes or iter_count passes run out;
+    // only_basic stops after singles and last remaining in a unit
+    fn propagate(&mut self, only_basic: bool, iter_count: usize, debug: bool, stats: &mut Option<&mut SolveStats>) -> Propagation {
         for _ in 0..iter_count {
-            let (mut changed, complete, no_solutions) = self.fill_naked_singles(debug);
+            let mut singles = (false, false, false);
+            self.tracked(Technique::NakedSingle, stats, |s| {
+                singles = s.fill_naked_singles(debug);
+                singles.0
+            });
+            let (mut changed, complete, no_solutions) = singles;
             if no_solutions {
-                return SolutionCount::Zero;
+                return Propagation::Contradiction;
             }
             if complete {
-                return SolutionCount::One(*self);
+                return Propagation::Complete;
             }
             if changed {
                 continue;
             }
 
-            // Last remaining in columns, rows, boxes
-            changed |= self.fill_last_in_column(debug);
-            changed |= self.fill_last_in_row(debug);
-            changed |= self.fill_last_in_box(debug);
-
-            if only_basic {
-                if !changed {
-                    break;
-                }
-                continue;
-            }
-
-            if changed {
-                continue;
-            }
-
-            // naked pairs (this can slow down the performance)
-            changed |= self.apply_naked_pairs(debug);
-            if changed {
-                continue;
-            }
-
-            // hidden pairs/trips (unknown effect on performance)
-            changed |= self.apply_hidden_pairs_columns(debug);
-            changed |= self.apply_hidden_pairs_rows(debug);
-            changed |= self.apply_hidden_pairs_boxes(debug);
-            if changed {
-                continue;
-            }
-
-            changed |= self.apply_hidden_trips_columns(debug);
-            changed |= self.apply_hidden_trips_rows(debug);
-            changed |= self.apply_hidden_trips_boxes(debug);
-
+            // then the first technique in Technique::ALL that changes anything, going back to
+            // the naked singles after it so every placement is credited to the easiest technique
+            let rungs = if only_basic { &Technique::ALL[1..=Technique::LastInBox as usize] } else { &Technique::ALL[1..] };
+            changed = rungs.iter().any(|&technique| self.tracked(technique, stats, |s| s.apply_technique(technique, debug)));
             if !changed {
                 break;
             }
         }
-        return SolutionCount::Zero;
+        Propagation::Stuck
     }
 
-    pub fn solve(&mut self, debug: bool) -> SolutionCount {
-        for _ in 0..100 {
-            let (mut changed, complete, no_solutions) = self.fill_naked_singles(debug);
-            if no_solutions {
-                return SolutionCount::Zero;
-            }
-            if complete {
-                return SolutionCount::One(*self);
-            }
-            if changed {
-                continue;
-            }
-
-            // Last remaining in columns, rows, boxes
-            changed |= self.fill_last_in_column(debug);
-            changed |= self.fill_last_in_row(debug);
-            changed |= self.fill_last_in_box(debug);
-
-            // skip naked pairs until we run out of easy changes
-            if changed {
-                continue;
-            }
-
-            // naked pairs (this can slow down the performance)
-            changed |= self.apply_naked_pairs(debug);
-
-            if changed {
-                continue;
-            }
-
-            // hidden pairs (unknown effect on performance)
-
-            changed |= self.apply_hidden_pairs_columns(debug);
-            changed |= self.apply_hidden_pairs_rows(debug);
-            changed |= self.apply_hidden_pairs_boxes(debug);
+    // Zero here also means the techniques got stuck before finishing
+    pub fn solve_no_guessing(&mut self, only_basic: bool, iter_count: usize, debug: bool) -> SolutionCount {
+        match self.propagate(only_basic, iter_count, debug, &mut None) {
+            Propagation::Complete => SolutionCount::One(*self),
+            Propagation::Contradiction | Propagation::Stuck => SolutionCount::Zero,
+        }
+    }
 
-            if changed {
-                continue;
-            }
+    pub fn solve(&mut self, debug: bool) -> SolutionCount {
+        self.search(debug, 0, &mut None)
+    }
 
-            // hidden trips (unlikely to help much; improves 0-guess solve count from 34115 to 34242 (with col), to 34359 (with col and row), to 34393 (with col, row, box) out of 49151)
-            // slows down performance of even the hardest 17-tile puzzles
-            changed |= self.apply_hidden_trips_columns(debug);
-            changed |= self.apply_hidden_trips_rows(debug);
-            changed |= self.apply_hidden_trips_boxes(debug);
+    // Same search as solve, with every node and technique counted
+    pub fn solve_with_stats(&mut self, debug: bool) -> (SolutionCount, SolveStats) {
+        let mut stats = SolveStats::default();
+        let solution_count = self.search(debug, 0, &mut Some(&mut stats));
+        (solution_count, stats)
+    }
 
-            if !changed {
-                break;
-            }
+    fn search(&mut self, debug: bool, depth: usize, stats: &mut Option<&mut SolveStats>) -> SolutionCount {
+        if let Some(stats) = stats {
+            stats.nodes += 1;
+            stats.max_depth = stats.max_depth.max(depth);
+        }
+        match self.propagate(false, 100, debug, stats) {
+            Propagation::Contradiction => {
+                if let Some(stats) = stats {
+                    stats.dead_ends += 1;
+                }
+                return SolutionCount::Zero;
+            },
+            Propagation::Complete => return SolutionCount::One(*self),
+            Propagation::Stuck => {},
         }
         let (best_x, best_y) = self.get_best_guess_spot(debug);
         let mut solution = None;
@@ -771,7 +560,10 @@ impl Sudoku {
             if self.is_possible_at(best_x, best_y, i) {
                 let mut new_sudoku = Self::from_sudoku(self);
                 new_sudoku.set_tile_at(best_x, best_y, Tile::Num(i));
-                let solution_count = new_sudoku.solve(debug);
+                if let Some(stats) = stats {
+                    stats.guesses += 1;
+                }
+                let solution_count = new_sudoku.search(debug, depth + 1, stats);
                 if debug {
                     println!("Guess: {}", i);
                     println!("{:?}", solution_count);
@@ -781,10 +573,10 @@ impl Sudoku {
                     SolutionCount::One(s) => {
                         match solution {
                             None => { solution = Some(s); }
-                            Some(_) => { return SolutionCount::Multiple }
+                            Some(first) => { return SolutionCount::Multiple(first, s) }
                         }
                     }
-                    SolutionCount::Multiple => { return SolutionCount::Multiple }
+                    SolutionCount::Multiple(first, second) => { return SolutionCount::Multiple(first, second) }
                 }
             }
         }
@@ -798,12 +590,92 @@ impl Sudoku {
     }
 }
 
+// Every completion of a grid, depth first from the same choice points as solve,
+// trying candidates in ascending order
+pub struct Solutions {
+    stack: Vec<Sudoku>,
+}
+
+impl Iterator for Solutions {
+    type Item = Sudoku;
+
+    fn next(&mut self) -> Option<Sudoku> {
+        while let Some(mut sudoku) = self.stack.pop() {
+            match sudoku.propagate(false, 100, false, &mut None) {
+                Propagation::Contradiction => {},
+                Propagation::Complete => return Some(sudoku),
+                Propagation::Stuck => {
+                    let (best_x, best_y) = sudoku.get_best_guess_spot(false);
+                    // pushed highest first so the lowest digit is explored first
+                    for i in (1..=9).rev() {
+                        if sudoku.is_possible_at(best_x, best_y, i) {
+                            let mut new_sudoku = sudoku;
+                            new_sudoku.set_tile_at(best_x, best_y, Tile::Num(i));
+                            self.stack.push(new_sudoku);
+                        }
+                    }
+                },
+            }
+        }
+        None
+    }
+}
+
+impl Sudoku {
+    pub fn solutions(&self) -> Solutions {
+        Solutions { stack: vec![*self] }
+    }
+
+    // Stops searching once limit solutions have been found
+    pub fn count_solutions(&self, limit: u64) -> u64 {
+        self.solutions().take(usize::try_from(limit).unwrap_or(usize::MAX)).count() as u64
+    }
+}
+
+// Solves every puzzle using up to `threads` worker threads (0 uses every core),
+// returning the results in the same order as the input
+pub fn solve_batch(all_sudoku: &[Sudoku], threads: usize) -> Vec<SolutionCount> {
+    let threads = match threads {
+        0 => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        n => n,
+    };
+    let threads = threads.min(all_sudoku.len().div_ceil(BATCH_CHUNK));
+    if threads <= 1 {
+        return all_sudoku.iter().map(|sudoku| Sudoku::from_sudoku(sudoku).solve(false)).collect();
+    }
+
+    let next_chunk = AtomicUsize::new(0);
+    let mut res = vec![SolutionCount::Zero; all_sudoku.len()];
+    thread::scope(|scope| {
+        let workers = (0..threads).map(|_| scope.spawn(|| {
+            let mut solved = vec![];
+            loop {
+                let start = next_chunk.fetch_add(BATCH_CHUNK, Ordering::Relaxed);
+                if start >= all_sudoku.len() {
+                    return solved;
+                }
+                let end = (start + BATCH_CHUNK).min(all_sudoku.len());
+                for (index, sudoku) in all_sudoku.iter().enumerate().take(end).skip(start) {
+                    solved.push((index, Sudoku::from_sudoku(sudoku).solve(false)));
+                }
+            }
+        })).collect::<Vec<_>>();
+        for worker in workers {
+            for (index, solution_count) in worker.join().expect("solver thread panicked") {
+                res[index] = solution_count;
+            }
+        }
+    });
+    res
+}
+
+// the older tests keep the helpers' return values around without checking them
 #[cfg(test)]
+#[allow(unused_variables)]
 mod tests {
     use super::*;
-    use crate::sudoku::*;
-    use crate::sudoku::solve::*;
     use crate::reader::*;
+    use crate::fixtures::*;
 
     #[test]
     fn test_get_naked_single() {
@@ -890,4 +762,259 @@ mod tests {
         assert_eq!(all_sudoku[0].possible[4][0], correct_257);
         assert_eq!(all_sudoku[0].possible[5][0], correct_257);
     }
+
+    #[test]
+    fn test_pointing() {
+        // 5 in the top left box only fits in its first row, 7 only in its middle column
+        let mut sudoku = Sudoku::new_blank();
+        for x in 0..3 {
+            for y in 0..3 {
+                if y != 0 {
+                    sudoku.remove_possible_at(x, y, 5);
+                }
+                if x != 1 {
+                    sudoku.remove_possible_at(x, y, 7);
+                }
+            }
+        }
+        assert!(sudoku.apply_pointing(false));
+        for i in 3..9 {
+            assert!(!sudoku.is_possible_at(i, 0, 5));
+            assert!(sudoku.is_possible_at(i, 1, 5));
+            assert!(!sudoku.is_possible_at(1, i, 7));
+            assert!(sudoku.is_possible_at(0, i, 7));
+        }
+        assert!(!sudoku.apply_pointing(false));
+    }
+
+    #[test]
+    fn test_claiming() {
+        // 4 in the middle row only fits in the centre box, 8 in the last column only in the bottom right box
+        let mut sudoku = Sudoku::new_blank();
+        for i in 0..9 {
+            if i / 3 != 1 {
+                sudoku.remove_possible_at(i, 4, 4);
+            }
+            if i / 3 != 2 {
+                sudoku.remove_possible_at(8, i, 8);
+            }
+        }
+        assert!(sudoku.apply_claiming(false));
+        for (x, y) in Unit::Box(4).cells() {
+            assert_eq!(sudoku.is_possible_at(x, y, 4), y == 4);
+        }
+        for (x, y) in Unit::Box(8).cells() {
+            assert_eq!(sudoku.is_possible_at(x, y, 8), x == 8);
+        }
+        assert!(sudoku.is_possible_at(0, 3, 4));
+        assert!(!sudoku.apply_claiming(false));
+    }
+
+    #[test]
+    fn test_naked_subsets() {
+        let mut sudoku = Sudoku::new_blank();
+        let mut keep_only = |x: usize, y: usize, digits: &[usize]| {
+            for val in (1..=9).filter(|val| !digits.contains(val)) {
+                sudoku.remove_possible_at(x, y, val);
+            }
+        };
+        // a triple with only two digits in each cell, in the first row and box
+        keep_only(0, 0, &[1, 2]);
+        keep_only(1, 0, &[2, 3]);
+        keep_only(2, 0, &[1, 3]);
+        // a quad spread along the last row
+        keep_only(0, 8, &[4, 5]);
+        keep_only(3, 8, &[5, 6]);
+        keep_only(6, 8, &[6, 7]);
+        keep_only(8, 8, &[4, 7]);
+
+        assert!(!sudoku.apply_naked_pairs(false));
+        assert!(sudoku.apply_naked_subsets(Unit::Row, 3, false));
+        assert!(sudoku.apply_naked_subsets(Unit::Box, 3, false));
+        for (x, y) in Unit::Row(0).cells().into_iter().chain(Unit::Box(0).cells()).filter(|&(x, y)| y != 0 || x > 2) {
+            assert_eq!(sudoku.possible[x][y] & 0b111, 0, "({}, {})", x, y);
+        }
+        assert_eq!(sudoku.possible[5][0], 0b111111000);
+        assert_eq!(sudoku.possible[1][0], 0b110);
+
+        assert!(!sudoku.apply_naked_subsets(Unit::Column, 4, false));
+        assert!(sudoku.apply_naked_subsets(Unit::Row, 4, false));
+        for x in [1, 2, 4, 5, 7] {
+            assert_eq!(sudoku.possible[x][8] & 0b1111000, 0);
+        }
+        assert_eq!(sudoku.possible[4][7], ALL_POSSIBLE);
+        assert!(!sudoku.apply_naked_subsets(Unit::Row, 4, false));
+    }
+
+    #[test]
+    fn test_hidden_subsets() {
+        let mut sudoku = Sudoku::new_blank();
+        // 1 and 2 only fit in the first two cells of the top row
+        for x in 2..9 {
+            sudoku.remove_possible_at(x, 0, 1);
+            sudoku.remove_possible_at(x, 0, 2);
+        }
+        // 3 to 6 only fit in every other cell of the last column
+        for y in [1, 3, 5, 7, 8] {
+            for val in 3..=6 {
+                sudoku.remove_possible_at(8, y, val);
+            }
+        }
+        assert!(!sudoku.apply_hidden_subsets(Unit::Column, 2, false));
+        assert!(sudoku.apply_hidden_subsets(Unit::Row, 2, false));
+        assert_eq!(sudoku.possible[0][0], 0b11);
+        assert_eq!(sudoku.possible[1][0], 0b11);
+        assert_eq!(sudoku.possible[2][0], 0b111111100);
+
+        assert!(sudoku.apply_hidden_subsets(Unit::Column, 4, false));
+        for y in [0, 2, 4, 6] {
+            assert_eq!(sudoku.possible[8][y], 0b111100);
+        }
+        assert_eq!(sudoku.possible[8][1], 0b111000011);
+        assert!(!sudoku.apply_hidden_subsets(Unit::Column, 4, false));
+        assert!(!sudoku.apply_hidden_subsets(Unit::Box, 3, false));
+    }
+
+    #[test]
+    fn test_fish() {
+        let mut sudoku = Sudoku::new_blank();
+        // X-Wing: 5 only fits in columns 2 and 7 of rows 1 and 4
+        for x in [0, 1, 3, 4, 5, 6, 8] {
+            sudoku.remove_possible_at(x, 1, 5);
+            sudoku.remove_possible_at(x, 4, 5);
+        }
+        // Swordfish: 9 only fits in rows 0, 4 and 8 of columns 0, 3 and 6, two rows each
+        for (x, keep) in [(0, [0, 4]), (3, [4, 8]), (6, [0, 8])] {
+            for y in (0..9).filter(|y| !keep.contains(y)) {
+                sudoku.remove_possible_at(x, y, 9);
+            }
+        }
+        assert!(!sudoku.apply_fish(Unit::Column, 2, false));
+        assert!(sudoku.apply_fish(Unit::Row, 2, false));
+        for y in 0..9 {
+            assert_eq!(sudoku.is_possible_at(2, y, 5), y == 1 || y == 4);
+            assert_eq!(sudoku.is_possible_at(7, y, 5), y == 1 || y == 4);
+        }
+        assert!(sudoku.is_possible_at(3, 0, 5));
+
+        assert!(sudoku.apply_fish(Unit::Column, 3, false));
+        for x in [1, 2, 4, 5, 7, 8] {
+            for y in [0, 4, 8] {
+                assert!(!sudoku.is_possible_at(x, y, 9));
+            }
+        }
+        assert!(sudoku.is_possible_at(0, 0, 9));
+        assert!(sudoku.is_possible_at(3, 8, 9));
+        assert!(sudoku.is_possible_at(1, 1, 9));
+        assert!(!sudoku.apply_fish(Unit::Column, 3, false));
+    }
+
+    #[test]
+    fn test_hardest_technique() {
+        let puzzle = Sudoku::from_string(X_WING);
+        let (solution_count, stats) = Sudoku::from_sudoku(&puzzle).solve_with_stats(false);
+        assert!(matches!(solution_count, SolutionCount::One(_)));
+        assert_eq!(stats.guesses, 0);
+        assert_eq!(stats.hardest_technique(), Some(Technique::XWing));
+
+        let (_, stats) = Sudoku::from_string(&format!("1{}", ".".repeat(80))).solve_with_stats(false);
+        assert!(stats.guesses > 0);
+        let (_, stats) = Sudoku::from_string(SOLUTION).solve_with_stats(false);
+        assert_eq!(stats.hardest_technique(), None);
+    }
+
+    #[test]
+    fn test_techniques_fire_easiest_first() {
+        let mut all_sudoku = get_all_sudoku_from_path("data(small)/sudoku17.csv").into_iter().take(300).collect::<Vec<_>>();
+        all_sudoku.push(Sudoku::from_string(X_WING));
+        for mut sudoku in all_sudoku {
+            let mut stats = SolveStats::default();
+            loop {
+                // one pass at a time, checking nothing easier than the technique that fired had anything to do
+                let before = sudoku;
+                let uses = stats.techniques.map(|count| count.uses);
+                let result = sudoku.propagate(false, 1, false, &mut Some(&mut stats));
+                let fired = Technique::ALL.into_iter().filter(|&t| stats.technique(t).uses != uses[t as usize]).collect::<Vec<_>>();
+                match fired.as_slice() {
+                    [] => break,
+                    &[technique] => {
+                        for easier in &Technique::ALL[..technique as usize] {
+                            let mut copy = before;
+                            assert!(!copy.apply_technique(*easier, false), "{} fired before {}", technique.name(), easier.name());
+                        }
+                    },
+                    _ => panic!("more than one technique in a pass: {:?}", fired),
+                }
+                if !matches!(result, Propagation::Stuck) {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_batch_keeps_order() {
+        let all_sudoku = get_all_sudoku_from_path("data(small)/sudoku17.csv").into_iter().take(100).collect::<Vec<_>>();
+        let sequential = solve_batch(&all_sudoku, 1);
+        assert_eq!(solve_batch(&all_sudoku, 4), sequential);
+        assert_eq!(solve_batch(&all_sudoku, 0), sequential);
+        assert!(sequential.iter().all(|s| matches!(s, SolutionCount::One(_))));
+        assert!(solve_batch(&[], 4).is_empty());
+    }
+
+    #[test]
+    fn test_multiple_returns_witnesses() {
+        let mut sudoku = Sudoku::from_string(TWO_SOLUTIONS);
+        let puzzle = sudoku;
+        let SolutionCount::Multiple(first, second) = sudoku.solve(false) else {
+            panic!("expected multiple solutions");
+        };
+        assert!(first.is_solution_of(&puzzle));
+        assert!(second.is_solution_of(&puzzle));
+        assert_eq!(first.differences(&second), vec![68, 71, 77, 80]);
+    }
+
+    #[test]
+    fn test_solutions_and_count() {
+        let solution = Sudoku::from_string(SOLUTION);
+        let puzzle = Sudoku::from_string(PUZZLE);
+        // solutions keep the clue count of the puzzle they came from
+        assert_eq!(puzzle.solutions().map(|s| s.board).collect::<Vec<_>>(), vec![solution.board]);
+        assert_eq!(solution.count_solutions(10), 1);
+
+        let two = Sudoku::from_string(TWO_SOLUTIONS);
+        let SolutionCount::Multiple(first, second) = Sudoku::from_sudoku(&two).solve(false) else {
+            panic!("expected multiple solutions");
+        };
+        assert_eq!(two.solutions().collect::<Vec<_>>(), vec![first, second]);
+        assert_eq!(two.count_solutions(10), 2);
+        assert_eq!(two.count_solutions(1), 1);
+        assert_eq!(two.count_solutions(0), 0);
+
+        // deterministic, and stops at the limit even with billions of completions
+        let empty = Sudoku::from_string(&".".repeat(81));
+        let some = empty.solutions().take(50).collect::<Vec<_>>();
+        assert_eq!(some, empty.solutions().take(50).collect::<Vec<_>>());
+        assert!(some.iter().all(|s| s.is_solved()));
+        assert!(some.windows(2).all(|w| w[0] != w[1]));
+        assert_eq!(empty.count_solutions(500), 500);
+    }
+
+    #[test]
+    fn test_solve_with_stats() {
+        let puzzle = Sudoku::from_string(PUZZLE);
+        let (solution_count, stats) = Sudoku::from_sudoku(&puzzle).solve_with_stats(false);
+        assert_eq!(solution_count, Sudoku::from_sudoku(&puzzle).solve(false));
+        assert_eq!((stats.nodes, stats.guesses, stats.max_depth, stats.dead_ends), (1, 0, 0, 0));
+        // without guessing, every empty cell is filled by some technique
+        let placements = stats.techniques.iter().map(|t| t.placements).sum::<usize>();
+        assert_eq!(placements, 81 - puzzle.clues);
+        assert!(stats.technique(Technique::NakedSingle).uses > 0);
+
+        // the 2/7 rectangle needs a guess at one corner, and both guesses complete the grid
+        let two = Sudoku::from_string(TWO_SOLUTIONS);
+        let (solution_count, stats) = Sudoku::from_sudoku(&two).solve_with_stats(false);
+        assert!(matches!(solution_count, SolutionCount::Multiple(..)));
+        assert_eq!((stats.nodes, stats.guesses, stats.max_depth, stats.dead_ends), (3, 2, 1, 0));
+    }
 }
@@ -0,0 +1,148 @@
+use crate::sudoku::*;
+
+// The techniques propagation applies, easiest first and in the order it tries them: singles,
+// then the box/line intersections, then subsets by size with naked before hidden, then fish by size
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Technique {
+    NakedSingle,
+    LastInColumn,
+    LastInRow,
+    LastInBox,
+    Pointing,
+    Claiming,
+    NakedPair,
+    NakedTriple,
+    HiddenPair,
+    HiddenTriple,
+    NakedQuad,
+    HiddenQuad,
+    XWing,
+    Swordfish,
+    Jellyfish,
+}
+
+impl Technique {
+    pub const ALL: [Technique; 15] = [
+        Technique::NakedSingle,
+        Technique::LastInColumn,
+        Technique::LastInRow,
+        Technique::LastInBox,
+        Technique::Pointing,
+        Technique::Claiming,
+        Technique::NakedPair,
+        Technique::NakedTriple,
+        Technique::HiddenPair,
+        Technique::HiddenTriple,
+        Technique::NakedQuad,
+        Technique::HiddenQuad,
+        Technique::XWing,
+        Technique::Swordfish,
+        Technique::Jellyfish,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Technique::NakedSingle => "naked single",
+            Technique::LastInColumn => "last in column",
+            Technique::LastInRow => "last in row",
+            Technique::LastInBox => "last in box",
+            Technique::Pointing => "pointing",
+            Technique::Claiming => "claiming",
+            Technique::NakedPair => "naked pair",
+            Technique::NakedTriple => "naked triple",
+            Technique::HiddenPair => "hidden pair",
+            Technique::HiddenTriple => "hidden triple",
+            Technique::NakedQuad => "naked quad",
+            Technique::HiddenQuad => "hidden quad",
+            Technique::XWing => "x-wing",
+            Technique::Swordfish => "swordfish",
+            Technique::Jellyfish => "jellyfish",
+        }
+    }
+}
+
+// Eliminations only count candidates removed from cells that are still empty afterwards,
+// so a placement's own candidates aren't counted twice
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct TechniqueCount {
+    // applications that changed the grid
+    pub uses: usize,
+    pub placements: usize,
+    pub eliminations: usize,
+}
+
+// Counts cover every node of the search, so with guesses > 0 the technique counts include
+// work done on guessed grids and hardest_technique is no grade for the puzzle itself
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct SolveStats {
+    // grids propagated by the search, the starting grid included
+    pub nodes: usize,
+    // candidates tried at choice points
+    pub guesses: usize,
+    // deepest stack of guesses
+    pub max_depth: usize,
+    // nodes where propagation hit a contradiction
+    pub dead_ends: usize,
+    // indexed like Technique::ALL
+    pub techniques: [TechniqueCount; Technique::ALL.len()],
+}
+
+impl SolveStats {
+    pub fn technique(&self, technique: Technique) -> TechniqueCount {
+        self.techniques[technique as usize]
+    }
+
+    // The highest ranked technique in Technique::ALL that changed anything at any node.
+    // Only meaningful as a grade when no guesses were needed
+    pub fn hardest_technique(&self) -> Option<Technique> {
+        Technique::ALL.into_iter().rev().find(|&technique| self.technique(technique).uses > 0)
+    }
+
+    // before is the grid's possible before the technique ran; cells that already had
+    // no candidates were either filled or a contradiction, and neither can change
+    pub(super) fn record(&mut self, technique: Technique, before: &[[u16; 9]; 9], after: &Sudoku) {
+        let mut placements = 0;
+        let mut eliminations = 0;
+        let cells = before.iter().flatten().zip(after.board.iter().flatten()).zip(after.possible.iter().flatten());
+        for ((&before, &tile), &possible) in cells {
+            if before == NONE_POSSIBLE {
+                continue;
+            }
+            if tile != Tile::Void {
+                placements += 1;
+            } else {
+                eliminations += (before & !possible).count_ones() as usize;
+            }
+        }
+        let count = &mut self.techniques[technique as usize];
+        count.uses += 1;
+        count.placements += placements;
+        count.eliminations += eliminations;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn techniques_are_ordered_by_difficulty() {
+        for (i, technique) in Technique::ALL.into_iter().enumerate() {
+            assert_eq!(technique as usize, i, "{}", technique.name());
+        }
+        let position = |technique| Technique::ALL.iter().position(|&t| t == technique).unwrap();
+        assert!(position(Technique::LastInBox) < position(Technique::Pointing));
+        assert!(position(Technique::Claiming) < position(Technique::NakedPair));
+        assert!(position(Technique::NakedPair) < position(Technique::NakedTriple));
+        assert!(position(Technique::NakedTriple) < position(Technique::HiddenPair));
+        assert!(position(Technique::HiddenTriple) < position(Technique::NakedQuad));
+        assert!(position(Technique::NakedQuad) < position(Technique::HiddenQuad));
+        assert!(position(Technique::HiddenQuad) < position(Technique::XWing));
+        assert!(position(Technique::Swordfish) < position(Technique::Jellyfish));
+
+        let mut stats = SolveStats::default();
+        stats.techniques[Technique::HiddenPair as usize].uses = 1;
+        stats.techniques[Technique::NakedTriple as usize].uses = 1;
+        assert_eq!(stats.hardest_technique(), Some(Technique::HiddenPair));
+    }
+}
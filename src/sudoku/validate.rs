@@ -0,0 +1,119 @@
+use std::fmt;
+
+use crate::sudoku::*;
+
+// Two cells of the same unit holding the same digit, as cell indices in
+// reading order (y * 9 + x) like ParseError::DuplicateDigit
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Conflict {
+    pub digit: usize,
+    pub first: usize,
+    pub second: usize,
+    pub unit: Unit,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "digit {} appears twice in {} (cells {} and {})", self.digit, self.unit, self.first, self.second)
+    }
+}
+
+impl Sudoku {
+    // Every clash, unit by unit in Unit::all order; a pair sharing a row and a box shows up once for each.
+    // A digit placed three times in a unit is reported against its first occurrence twice
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut res = vec![];
+        for unit in Unit::all() {
+            let mut seen = [None; 9];
+            for (x, y) in unit.cells() {
+                if let Tile::Num(digit) = self.board[x][y] {
+                    let cell = y * 9 + x;
+                    match seen[digit - 1] {
+                        Some(first) => res.push(Conflict { digit, first, second: cell, unit }),
+                        None => seen[digit - 1] = Some(cell),
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.board.iter().flatten().all(|&tile| tile != Tile::Void)
+    }
+
+    // No unit holds a digit twice; empty cells are fine
+    pub fn is_valid(&self) -> bool {
+        self.conflicts().is_empty()
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.is_complete() && self.is_valid()
+    }
+
+    // Cells (y * 9 + x) whose tiles differ, in reading order
+    pub fn differences(&self, other: &Sudoku) -> Vec<usize> {
+        (0..81).filter(|&cell| self.board[cell % 9][cell / 9] != other.board[cell % 9][cell / 9]).collect()
+    }
+
+    // A solved grid that keeps every clue of the puzzle
+    pub fn is_solution_of(&self, puzzle: &Sudoku) -> bool {
+        let keeps_clues = self.board.iter().flatten().zip(puzzle.board.iter().flatten())
+            .all(|(&tile, &clue)| clue == Tile::Void || tile == clue);
+        keeps_clues && self.is_solved()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn unit_cells_match_masks() {
+        let sudoku = Sudoku::from_string(PUZZLE);
+        for unit in Unit::all() {
+            for num in 1..=9 {
+                let mask = sudoku.unit_possible(unit, num);
+                for (k, (x, y)) in unit.cells().into_iter().enumerate() {
+                    let possible = sudoku.possible[x][y] & (1 << (num - 1)) != 0;
+                    assert_eq!(mask & (1 << k) != 0, possible, "{} cell {} digit {}", unit, k, num);
+                }
+            }
+        }
+        assert_eq!(Unit::all().count(), 27);
+        assert_eq!(Unit::containing(4, 7), [Unit::Row(7), Unit::Column(4), Unit::Box(7)]);
+    }
+
+    #[test]
+    fn finds_conflicts() {
+        let mut sudoku = Sudoku::from_string(PUZZLE);
+        assert!(sudoku.conflicts().is_empty());
+        assert!(sudoku.is_valid());
+        assert!(!sudoku.is_complete());
+
+        // a second 2 in the top left box and first column
+        sudoku.board[1][1] = Tile::Num(2);
+        sudoku.board[0][4] = Tile::Num(2);
+        assert_eq!(sudoku.conflicts(), vec![
+            Conflict { digit: 2, first: 0, second: 36, unit: Unit::Column(0) },
+            Conflict { digit: 2, first: 0, second: 10, unit: Unit::Box(0) },
+        ]);
+        assert!(!sudoku.is_valid());
+        assert_eq!(sudoku.conflicts()[0].to_string(), "digit 2 appears twice in column 1 (cells 0 and 36)");
+    }
+
+    #[test]
+    fn checks_solutions() {
+        let puzzle = Sudoku::from_string(PUZZLE);
+        let solution = Sudoku::from_string(SOLUTION);
+        assert!(solution.is_solved());
+        assert!(solution.is_solution_of(&puzzle));
+        assert!(!puzzle.is_solved());
+
+        // swapping two digits everywhere keeps the grid valid but breaks the clues
+        let swapped = Sudoku::from_string(SWAPPED_SOLUTION);
+        assert!(swapped.is_solved());
+        assert!(!swapped.is_solution_of(&puzzle));
+    }
+}
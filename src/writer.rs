@@ -0,0 +1,137 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::sudoku::*;
+use crate::reader::*;
+
+pub const COLUMNS: [&str; 5] = ["id", "sudoku", "solution", "clues", "difficulty"];
+
+// Writes the same schema PuzzleReader reads: id,sudoku,solution,clues,difficulty
+// followed by any extra columns, which are looked up in PuzzleRecord::extra
+pub struct PuzzleWriter<W: Write> {
+    inner: W,
+    delimiter: char,
+    extra_columns: Vec<String>,
+    rows_written: usize,
+}
+
+impl PuzzleWriter<BufWriter<File>> {
+    // paths ending in .tsv are written tab separated
+    pub fn create(path: &str) -> io::Result<Self> {
+        let delimiter = if path.ends_with(".tsv") { '\t' } else { ',' };
+        let file = File::create(path)?;
+        Self::with_columns(BufWriter::new(file), delimiter, vec![])
+    }
+}
+
+impl<W: Write> PuzzleWriter<W> {
+    pub fn new(inner: W) -> io::Result<Self> {
+        Self::with_columns(inner, ',', vec![])
+    }
+
+    pub fn with_columns(inner: W, delimiter: char, extra_columns: Vec<String>) -> io::Result<Self> {
+        let mut res = Self { inner, delimiter, extra_columns, rows_written: 0 };
+        let mut header = COLUMNS.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        header.extend(res.extra_columns.iter().cloned());
+        res.write_row(&header)?;
+        Ok(res)
+    }
+
+    pub fn write_record(&mut self, record: &PuzzleRecord) -> io::Result<()> {
+        self.rows_written += 1;
+        // rows without an id get their 1-based row number
+        let id = record.id.clone().unwrap_or_else(|| self.rows_written.to_string());
+        let solution = match record.solution {
+            Some(solution) => solution.to_digit_string(),
+            None => "TODO".to_string(),
+        };
+        let rating = record.rating.map(|r| r.to_string()).unwrap_or_default();
+
+        let mut row = vec![id, record.puzzle.to_digit_string(), solution, record.puzzle.clues.to_string(), rating];
+        for column in &self.extra_columns {
+            row.push(record.extra.get(column).cloned().unwrap_or_default());
+        }
+        self.write_row(&row)
+    }
+
+    pub fn write_sudoku(&mut self, sudoku: &Sudoku) -> io::Result<()> {
+        self.write_record(&PuzzleRecord::new(*sudoku))
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn write_row(&mut self, row: &[String]) -> io::Result<()> {
+        // there is no quoting, so fields must not contain the delimiter or a newline
+        if let Some(bad) = row.iter().find(|field| field.contains(self.delimiter) || field.contains('\n')) {
+            let message = format!("field {:?} cannot be written without quoting", bad);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+        writeln!(self.inner, "{}", row.join(&self.delimiter.to_string()))
+    }
+}
+
+pub fn write_all_sudoku_to_path(path: &str, all_sudoku: &[Sudoku]) -> io::Result<()> {
+    let mut writer = PuzzleWriter::create(path)?;
+    for sudoku in all_sudoku {
+        writer.write_sudoku(sudoku)?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn writes_reader_schema() {
+        let mut writer = PuzzleWriter::new(vec![]).unwrap();
+        writer.write_sudoku(&Sudoku::from_string(PUZZLE)).unwrap();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("id,sudoku,solution,clues,difficulty"));
+        assert_eq!(lines.next(), Some(format!("1,{},TODO,26,", PUZZLE.replace('0', ".")).as_str()));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn round_trips_through_reader() {
+        let mut record = PuzzleRecord::new(Sudoku::from_string(PUZZLE));
+        record.id = Some("puzzle-7".to_string());
+        record.solution = Some(Sudoku::from_string(SOLUTION));
+        record.rating = Some(2.5);
+        record.extra.insert("source".to_string(), "basic-test".to_string());
+
+        for delimiter in [',', '\t'] {
+            let mut writer = PuzzleWriter::with_columns(vec![], delimiter, vec!["source".to_string()]).unwrap();
+            writer.write_record(&record).unwrap();
+            writer.write_sudoku(&Sudoku::from_string(SOLUTION)).unwrap();
+            let output = writer.into_inner();
+
+            let read = PuzzleReader::new(output.as_slice()).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(read.len(), 2);
+            assert_eq!(read[0].id, record.id);
+            assert_eq!(read[0].puzzle, record.puzzle);
+            assert_eq!(read[0].solution, record.solution);
+            assert_eq!(read[0].rating, record.rating);
+            assert_eq!(read[0].extra.get("source"), record.extra.get("source"));
+            assert_eq!(read[0].extra.get("clues").map(String::as_str), Some("26"));
+            assert_eq!(read[1].id.as_deref(), Some("2"));
+            assert_eq!(read[1].solution, None);
+        }
+    }
+
+    #[test]
+    fn rejects_fields_needing_quotes() {
+        let mut record = PuzzleRecord::new(Sudoku::from_string(PUZZLE));
+        record.id = Some("a,b".to_string());
+        let mut writer = PuzzleWriter::new(vec![]).unwrap();
+        assert!(writer.write_record(&record).is_err());
+    }
+}